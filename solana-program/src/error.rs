@@ -1,16 +1,176 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::program_error::ProgramError;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive as _;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+use thiserror::Error;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+/// Errors returned by the OtakuVerse program
+///
+/// Discriminants are part of the program's public interface and are surfaced to
+/// clients as `ProgramError::Custom(code)`. Never reorder or reuse them; add new
+/// variants at the end with the next free code, and mirror them in the web client's
+/// decoder table (`web/src/utils/programErrors.ts`).
+#[derive(Error, FromPrimitive, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OtakuVerseError {
-    InvalidInstructionData,
-    InsufficientFunds,
-    Unauthorized,
-    NFTAlreadyExists,
-    NFTNotFound,
-    InvalidNFTMetadata,
-    InvalidMessageData,
-    CommunityNotFound,
+    #[error("Invalid instruction data")]
+    InvalidInstructionData = 0,
+    #[error("Insufficient funds")]
+    InsufficientFunds = 1,
+    #[error("Unauthorized")]
+    Unauthorized = 2,
+    #[error("NFT already exists")]
+    NFTAlreadyExists = 3,
+    #[error("NFT not found")]
+    NFTNotFound = 4,
+    #[error("Invalid NFT metadata")]
+    InvalidNFTMetadata = 5,
+    #[error("Invalid message data")]
+    InvalidMessageData = 6,
+    #[error("Community not found")]
+    CommunityNotFound = 7,
+    #[error("Signer does not own the NFT")]
+    NotOwner = 8,
+    #[error("NFT is not listed for sale")]
+    NotListed = 9,
+    #[error("NFT is already listed for sale")]
+    AlreadyListed = 10,
+    #[error("Price does not match the listing")]
+    PriceMismatch = 11,
+    #[error("String exceeds its maximum length")]
+    StringTooLong = 12,
+    #[error("Account is already initialized")]
+    AccountAlreadyInitialized = 13,
+    #[error("Account is not owned by the expected program")]
+    WrongAccountOwner = 14,
+    #[error("Account does not match its expected program address")]
+    InvalidPDA = 15,
+    #[error("Basis points must not exceed 10000")]
+    InvalidBasisPoints = 16,
+    #[error("Price must be greater than zero")]
+    InvalidPrice = 17,
+    #[error("Mint does not match the NFT")]
+    MintMismatch = 18,
+    #[error("Signer is not a platform admin")]
+    NotAdmin = 19,
+    #[error("Admin is already registered")]
+    AdminAlreadyExists = 20,
+    #[error("Admin is not registered")]
+    AdminNotFound = 21,
+    #[error("Admin registry is full")]
+    AdminListFull = 22,
+    #[error("Treasury does not match the program config")]
+    TreasuryMismatch = 23,
+    #[error("Creator does not match the NFT")]
+    CreatorMismatch = 24,
+    #[error("Community already exists")]
+    CommunityAlreadyExists = 25,
+    #[error("Signer is not the community creator")]
+    NotCommunityCreator = 26,
+    #[error("Signer is not a community moderator")]
+    NotModerator = 27,
+    #[error("User is not a community member")]
+    NotMember = 28,
+    #[error("User is already a community member")]
+    AlreadyMember = 29,
+    #[error("User is banned from the community")]
+    UserBanned = 30,
+    #[error("User cannot be banned by this signer")]
+    CannotBan = 31,
+    #[error("Moderator is already appointed")]
+    ModeratorAlreadyExists = 32,
+    #[error("Moderator is not appointed")]
+    ModeratorNotFound = 33,
+    #[error("Moderator list is full")]
+    ModeratorListFull = 34,
+    #[error("Message is empty")]
+    EmptyMessage = 35,
+    #[error("Reply target is not a message in this community")]
+    InvalidReplyTarget = 36,
+    #[error("Message has reached its limit of distinct reactions")]
+    TooManyReactions = 37,
+    #[error("Reaction already exists")]
+    ReactionAlreadyExists = 38,
+    #[error("Arithmetic overflow")]
+    MathOverflow = 39,
+    #[error("Unsupported instruction version")]
+    UnsupportedInstructionVersion = 40,
+    #[error("Unknown instruction")]
+    UnknownInstruction = 41,
+    #[error("Unsupported account version")]
+    UnsupportedAccountVersion = 42,
+    #[error("Account must be migrated to the current layout")]
+    AccountNeedsMigration = 43,
+    #[error("NFT name exceeds 32 bytes")]
+    NameTooLong = 44,
+    #[error("NFT symbol exceeds 10 bytes")]
+    SymbolTooLong = 45,
+    #[error("NFT URI exceeds 200 bytes")]
+    UriTooLong = 46,
+    #[error("Anime title exceeds 64 bytes")]
+    AnimeTitleTooLong = 47,
+    #[error("NFT description exceeds 256 bytes")]
+    DescriptionTooLong = 48,
+    #[error("NFT has more than 16 attributes")]
+    TooManyAttributes = 49,
+    #[error("Attribute trait type or value is too long")]
+    AttributeTooLong = 50,
+    #[error("Unknown NFT rarity")]
+    InvalidRarity = 51,
+    #[error("Supply cap for this rarity has been reached")]
+    RaritySupplyExhausted = 52,
+    #[error("Supply cap is below the number already minted")]
+    InvalidSupplyCap = 53,
+    #[error("Signer is not the collection authority")]
+    NotCollectionAuthority = 54,
+    #[error("NFT already has an attribute with this trait type")]
+    DuplicateAttribute = 55,
+    #[error("NFT has no attribute with this trait type")]
+    AttributeNotFound = 56,
+    #[error("Attribute value does not match its display type")]
+    InvalidAttributeValue = 57,
+    #[error("NFT has more than 5 creators")]
+    TooManyCreators = 58,
+    #[error("Creator shares must sum to 100")]
+    InvalidCreatorShares = 59,
+    #[error("Creator is listed more than once")]
+    DuplicateCreator = 60,
+    #[error("Offer has expired")]
+    OfferExpired = 61,
+    #[error("Expiry must be in the future")]
+    InvalidExpiry = 62,
+    #[error("Auction is not accepting bids")]
+    AuctionNotActive = 63,
+    #[error("Auction has not ended")]
+    AuctionNotEnded = 64,
+    #[error("Bid is below the reserve price or minimum increment")]
+    BidTooLow = 65,
+    #[error("Auction must end after it starts, in the future")]
+    InvalidAuctionSchedule = 66,
+    #[error("Account is not the auction's highest bidder")]
+    BidderMismatch = 67,
+    #[error("Declining price must fall to a nonzero lower floor over a non-empty window")]
+    InvalidPriceSchedule = 68,
+    #[error("Payment mint is not accepted")]
+    PaymentMintNotAccepted = 69,
+    #[error("Payment mint is already accepted")]
+    PaymentMintAlreadyAccepted = 70,
+    #[error("Payment mint allowlist is full")]
+    PaymentMintListFull = 71,
+    #[error("NFT is already a master edition or a print")]
+    AlreadyAnEdition = 72,
+    #[error("NFT is not a master edition")]
+    NotMasterEdition = 73,
+    #[error("Edition accounts do not match the NFT's edition")]
+    EditionMismatch = 74,
+    #[error("Soulbound NFTs cannot be transferred")]
+    SoulboundNFT = 75,
+    #[error("NFT is not soulbound")]
+    NotSoulbound = 76,
+    #[error("Supply caps have not been set for this anime")]
+    RaritySupplyNotSet = 77,
 }
 
 impl From<OtakuVerseError> for ProgramError {
@@ -18,3 +178,30 @@ impl From<OtakuVerseError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for OtakuVerseError {
+    fn type_of() -> &'static str {
+        "OtakuVerseError"
+    }
+}
+
+impl PrintProgramError for OtakuVerseError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + num_traits::FromPrimitive,
+    {
+        msg!("Error: {}", self);
+    }
+}
+
+impl OtakuVerseError {
+    /// Decode a `ProgramError::Custom` code returned by the program
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::from_u32(code)
+    }
+
+    /// Human-readable message for a custom error code, for clients displaying failures
+    pub fn message_for_code(code: u32) -> Option<String> {
+        Self::from_code(code).map(|e| e.to_string())
+    }
+}
@@ -1,47 +1,88 @@
 use borsh::{BorshSerialize, BorshDeserialize};
-use solana_program::pubkey::Pubkey;
-use crate::state::nft::{NFTAttribute, NFTRarity};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use crate::error::OtakuVerseError;
+use crate::state::{
+    account::LegacyAccountType,
+    community::{BanData, CommunityData, MembershipData, MessageData, ReactionData},
+    config::ProgramConfig,
+    nft::{
+        ListingType, NFTAttribute, NFTAttributeV1, NFTCreator, NFTData, NFTEdition, NFTRarity,
+    },
+    supply::RaritySupplyData,
+    collection::CollectionData,
+    offer::OfferData,
+    auction::AuctionData,
+    redemption::RedemptionData,
+};
 use solana_program::instruction::{Instruction, AccountMeta};
+use solana_program::bpf_loader_upgradeable;
 use solana_program::system_program;
 use solana_program::sysvar::rent;
+use spl_associated_token_account::get_associated_token_address;
 use spl_token;
 use mpl_token_metadata;
+use mpl_token_metadata::pda::{find_master_edition_account, find_metadata_account};
+use mpl_token_metadata::state::{EDITION, EDITION_MARKER_BIT_SIZE, PREFIX};
 
 /// Instructions supported by the OtakuVerse program
-#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+///
+/// Instruction data is encoded with [`OtakuVerseInstruction::pack`] and decoded with
+/// [`OtakuVerseInstruction::unpack`]. The legacy wire format is the Borsh encoding of
+/// the original variants with their original fields, so variants must never be
+/// reordered; fields appended to them since are optional trailing fields. New variants
+/// go at the end and get an explicit discriminator in [`discriminator`].
+#[derive(Clone, Debug, PartialEq)]
 pub enum OtakuVerseInstruction {
     /// Mint a new NFT as a reward for watching anime
     /// Accounts expected:
-    /// 0. `[signer]` The authority account (payer)
+    /// 0. `[signer, writable]` The authority account (payer)
     /// 1. `[writable]` The NFT mint account
     /// 2. `[writable]` The NFT token account
     /// 3. `[writable]` The NFT metadata account
-    /// 4. `[]` The mint authority
-    /// 5. `[]` The update authority
+    /// 4. `[]` The mint authority (the freeze authority if soulbound)
+    /// 5. `[]` The update authority (a signer unless soulbound)
     /// 6. `[]` The system program
     /// 7. `[]` The token program
     /// 8. `[]` The token metadata program
     /// 9. `[]` The rent sysvar
+    /// 10. `[writable]` The NFT data account (PDA of `["nft_data", mint]`)
+    /// 11. `[writable]` The rarity supply account (PDA of `["rarity_supply", hash(anime_title)]`,
+    ///     created by SetRaritySupplyCaps)
+    /// 12. If soulbound, `[]` the freeze authority (PDA of `["soulbound", mint]`), otherwise
+    ///     `[writable]` the master edition account, created with no prints allowed
     MintNFTReward {
         name: String,
         symbol: String,
         uri: String,
         anime_title: String,
+        /// One of "common", "rare", "epic" or "legendary" (case-insensitive)
         rarity: String,
+        /// Freeze the reward in the recipient's token account, transferable if omitted
+        soulbound: bool,
     },
 
     /// Mint a new NFT with enhanced metadata
     /// Accounts expected:
-    /// 0. `[signer]` The authority account (payer)
+    /// 0. `[signer, writable]` The authority account (payer)
     /// 1. `[writable]` The NFT mint account
     /// 2. `[writable]` The NFT token account
     /// 3. `[writable]` The NFT metadata account
-    /// 4. `[]` The mint authority
-    /// 5. `[]` The update authority
+    /// 4. `[signer]` The mint authority
+    /// 5. `[signer]` The update authority
     /// 6. `[]` The system program
     /// 7. `[]` The token program
     /// 8. `[]` The token metadata program
     /// 9. `[]` The rent sysvar
+    /// 10. `[writable]` The NFT data account (PDA of `["nft_data", mint]`)
+    /// 11. `[writable]` The rarity supply account (PDA of `["rarity_supply", hash(anime_title)]`,
+    ///     created by SetRaritySupplyCaps)
+    /// 12. `[writable]` The master edition account
+    ///
+    /// When minting into a collection, also:
+    /// 13. `[writable]` The collection account (PDA of `["collection", collection_mint]`)
+    /// 14. `[]` The collection mint account
+    /// 15. `[writable]` The collection metadata account
+    /// 16. `[]` The collection master edition account
     MintEnhancedNFT {
         name: String,
         symbol: String,
@@ -49,32 +90,59 @@ pub enum OtakuVerseInstruction {
         anime_title: String,
         rarity: NFTRarity,
         description: String,
+        /// Mint of the collection to verify the NFT into (the authority must own the collection)
         collection_id: Option<Pubkey>,
-        attributes: Vec<NFTAttribute>,
+        /// Initial attributes, with unique trait types
+        attributes: Vec<NFTAttributeV1>,
         royalty_basis_points: u16,
+        /// Creators sharing the royalties, with shares summing to 100
+        /// (omitted by older clients, in which case the authority gets the full share)
+        creators: Vec<NFTCreator>,
+        /// Initial attributes with display types, replacing any in `attributes` with the
+        /// same trait type (omitted by older clients)
+        typed_attributes: Vec<NFTAttribute>,
+        /// Editions that may be printed from the NFT, unlimited if None (no prints when
+        /// omitted by older clients)
+        max_supply: Option<u64>,
     },
 
-    /// Purchase an NFT from the marketplace
+    /// Purchase a listed NFT from the marketplace
     ///
     /// Accounts expected:
-    /// 0. `[signer]` The buyer account (payer)
+    /// 0. `[signer, writable]` The buyer account (payer)
     /// 1. `[writable]` The seller account (receiver)
-    /// 2. `[writable]` The NFT mint account
+    /// 2. `[]` The NFT mint account
     /// 3. `[writable]` The buyer's NFT token account
-    /// 4. `[writable]` The seller's NFT token account
-    /// 5. `[]` The system program
-    /// 6. `[]` The token program
+    /// 4. `[writable]` The NFT data account
+    /// 5. `[writable]` The escrow NFT token account
+    /// 6. `[]` The escrow authority
+    /// 7. `[]` The program config account
+    /// 8. `[writable]` The treasury account (platform fee receiver)
+    /// 9. `[]` The system program
+    /// 10. `[]` The token program
+    /// 11.. `[writable]` One account per NFT creator, in the NFT data's order (royalty receivers)
+    ///
+    /// If the listing is priced in an SPL token, accounts 11.. are instead:
+    /// 11. `[writable]` The buyer's payment token account
+    /// 12. `[writable]` The seller's payment token account
+    /// 13. `[writable]` The treasury's payment token account
+    /// 14.. `[writable]` One payment token account per NFT creator, in the NFT data's order
     PurchaseNFT {
-        /// Price in lamports
+        /// Price in lamports or payment token base units, must match a fixed listing price;
+        /// for a declining-price listing it is the most the buyer will pay, and only the
+        /// current price is charged
         price: u64,
     },
 
-    /// Update NFT metadata
+    /// Update NFT metadata, both the stored NFT data and the token metadata
     ///
     /// Accounts expected:
-    /// 0. `[signer]` The owner account
-    /// 1. `[writable]` The NFT metadata account
+    /// 0. `[signer, writable]` The metadata update authority (payer if the data grows)
+    /// 1. `[writable]` The NFT data account
     /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The NFT metadata account
+    /// 4. `[]` The token metadata program
+    /// 5. `[]` The system program
     UpdateNFTMetadata {
         /// New URI pointing to the NFT metadata
         uri: Option<String>,
@@ -82,58 +150,1182 @@ pub enum OtakuVerseInstruction {
         name: Option<String>,
         /// New description of the NFT
         description: Option<String>,
-        /// Attributes to add to the NFT
-        attributes_to_add: Vec<NFTAttribute>,
+        /// Attributes to add to the NFT, replacing any with the same trait type
+        attributes_to_add: Vec<NFTAttributeV1>,
         /// Royalty percentage for secondary sales (in basis points)
         royalty_basis_points: Option<u16>,
+        /// Attributes with display types to add after `attributes_to_add`, replacing any
+        /// with the same trait type (omitted by older clients)
+        typed_attributes_to_add: Vec<NFTAttribute>,
     },
 
     /// Verify an NFT
     ///
     /// Accounts expected:
     /// 0. `[signer]` The authority account (must be OtakuVerse admin)
-    /// 1. `[writable]` The NFT metadata account
+    /// 1. `[writable]` The NFT data account
     /// 2. `[]` The NFT mint account
+    /// 3. `[]` The program config account
     VerifyNFT {
         /// Mint address of the NFT to verify
         mint: Pubkey,
     },
 
-    /// List an NFT for sale
+    /// List an NFT for sale, moving it into the program's escrow
     ///
     /// Accounts expected:
-    /// 0. `[signer]` The owner account
-    /// 1. `[writable]` The NFT metadata account
+    /// 0. `[signer, writable]` The owner account (payer)
+    /// 1. `[writable]` The NFT data account
     /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The owner's NFT token account
+    /// 4. `[writable]` The escrow NFT token account (ATA of the escrow authority)
+    /// 5. `[]` The escrow authority (PDA of `["escrow", mint]`)
+    /// 6. `[]` The system program
+    /// 7. `[]` The token program
+    /// 8. `[]` The associated token account program
+    /// 9. `[]` The program config account (required if priced in an SPL token)
     ListNFTForSale {
-        /// Price in lamports
+        /// Price in lamports or payment token base units (the starting price of a
+        /// declining-price listing)
         price: u64,
+        /// How the listing is priced, fixed if omitted
+        listing_type: ListingType,
+        /// SPL token mint the listing is priced in, lamports if omitted
+        payment_mint: Option<Pubkey>,
     },
 
-    /// Cancel an NFT listing
+    /// Cancel an NFT listing, returning the NFT from escrow
     ///
     /// Accounts expected:
-    /// 0. `[signer]` The owner account
-    /// 1. `[writable]` The NFT metadata account
+    /// 0. `[signer, writable]` The owner account (receives the escrow rent)
+    /// 1. `[writable]` The NFT data account
     /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The owner's NFT token account
+    /// 4. `[writable]` The escrow NFT token account
+    /// 5. `[]` The escrow authority
+    /// 6. `[]` The token program
     CancelNFTListing,
 
     /// Send a message in a community chat
     ///
+    /// The message is stored at the community's next message index.
+    ///
     /// Accounts expected:
-    /// 0. `[signer]` The sender account
+    /// 0. `[signer, writable]` The sender account (payer)
     /// 1. `[writable]` The community account
-    /// 2. `[writable]` The message account
+    /// 2. `[writable]` The message account (PDA of `["message", community, index]`)
     /// 3. `[]` The system program
+    /// 4. `[]` The sender's membership account
+    /// 5. `[]` The sender's ban record account (must not exist)
+    /// 6. `[writable]` The message being replied to (only if `reply_to` is set)
     SendCommunityMessage {
         /// Community ID
         community_id: String,
         /// Message content
         content: String,
+        /// Index of the message this replies to
+        reply_to: Option<u64>,
+    },
+
+    /// Create the program config
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The super admin account (payer)
+    /// 1. `[writable]` The program config account (PDA of `["config"]`)
+    /// 2. `[]` The system program
+    /// 3. `[]` The program's ProgramData account (its upgrade authority must be the signer)
+    InitializeConfig {
+        /// The account receiving platform fees
+        treasury: Pubkey,
+        /// Platform fee on marketplace sales (in basis points)
+        platform_fee_basis_points: u16,
+    },
+
+    /// Update the program config
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The super admin account
+    /// 1. `[writable]` The program config account
+    UpdateConfig {
+        /// New treasury account
+        treasury: Option<Pubkey>,
+        /// New platform fee (in basis points)
+        platform_fee_basis_points: Option<u16>,
+    },
+
+    /// Revoke an NFT's verification
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The authority account (must be OtakuVerse admin)
+    /// 1. `[writable]` The NFT data account
+    /// 2. `[]` The NFT mint account
+    /// 3. `[]` The program config account
+    UnverifyNFT {
+        /// Mint address of the NFT to unverify
+        mint: Pubkey,
+    },
+
+    /// Add a platform admin
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The super admin account
+    /// 1. `[writable]` The program config account
+    AddAdmin {
+        /// The admin to add
+        admin: Pubkey,
+    },
+
+    /// Remove a platform admin
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The super admin account
+    /// 1. `[writable]` The program config account
+    RemoveAdmin {
+        /// The admin to remove
+        admin: Pubkey,
+    },
+
+    /// Create a community
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The creator account (payer)
+    /// 1. `[writable]` The community account (PDA of `["community", id]`)
+    /// 2. `[]` The system program
+    CreateCommunity {
+        /// Community ID, unique across the program
+        id: String,
+        /// Community name
+        name: String,
+        /// Community description
+        description: String,
+    },
+
+    /// Update a community's details
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The creator account
+    /// 1. `[writable]` The community account
+    UpdateCommunity {
+        /// New community name
+        name: Option<String>,
+        /// New community description
+        description: Option<String>,
+    },
+
+    /// Close a community and reclaim most of its rent
+    ///
+    /// The account is kept as a small tombstone so the community ID cannot be reused.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The creator account
+    /// 1. `[writable]` The community account
+    CloseCommunity,
+
+    /// Join a community
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The user account (payer)
+    /// 1. `[writable]` The community account
+    /// 2. `[writable]` The membership account (PDA of `["membership", community, user]`)
+    /// 3. `[]` The system program
+    /// 4. `[]` The user's ban record account (must not exist)
+    JoinCommunity,
+
+    /// Leave a community and reclaim the membership rent, also once the community is closed
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The user account
+    /// 1. `[writable]` The community account
+    /// 2. `[writable]` The membership account
+    LeaveCommunity,
+
+    /// Appoint a community moderator
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The creator account
+    /// 1. `[writable]` The community account
+    AddModerator {
+        /// The moderator to add
+        moderator: Pubkey,
+    },
+
+    /// Remove a community moderator
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The creator account
+    /// 1. `[writable]` The community account
+    RemoveModerator {
+        /// The moderator to remove
+        moderator: Pubkey,
+    },
+
+    /// Transfer community ownership to a new creator
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The creator account
+    /// 1. `[writable]` The community account
+    TransferCommunityOwnership {
+        /// The new creator
+        new_creator: Pubkey,
+    },
+
+    /// Hide or unhide a community message
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The moderator account
+    /// 1. `[]` The community account
+    /// 2. `[writable]` The message account
+    SetMessageHidden {
+        /// Whether the message should be hidden
+        hidden: bool,
+    },
+
+    /// Delete a community message, refunding its rent to the sender
+    ///
+    /// Once the community is closed, anyone can delete its messages.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The moderator account
+    /// 1. `[]` The community account
+    /// 2. `[writable]` The message account
+    /// 3. `[writable]` The message sender account
+    DeleteMessage,
+
+    /// Ban a user from a community, removing their membership if any
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The moderator account (payer)
+    /// 1. `[writable]` The community account
+    /// 2. `[writable]` The ban record account (PDA of `["ban", community, user]`)
+    /// 3. `[writable]` The user's membership account (PDA of `["membership", community, user]`,
+    ///    empty if the user is not a member)
+    /// 4. `[writable]` The user account (receives the membership rent)
+    /// 5. `[]` The system program
+    BanMember {
+        /// The user to ban
+        user: Pubkey,
+    },
+
+    /// Lift a user's ban from a community
+    ///
+    /// Once the community is closed, anyone can lift its bans to refund their rent.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The moderator account
+    /// 1. `[]` The community account
+    /// 2. `[writable]` The ban record account
+    /// 3. `[writable]` The moderator who created the ban (receives the ban record rent)
+    UnbanMember {
+        /// The user to unban
+        user: Pubkey,
+    },
+
+    /// React to a community message
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The user account (payer)
+    /// 1. `[]` The community account
+    /// 2. `[writable]` The message account
+    /// 3. `[writable]` The reaction account (PDA of `["reaction", message, user, emoji_code]`)
+    /// 4. `[]` The user's membership account
+    /// 5. `[]` The system program
+    AddReaction {
+        /// Unicode code point of the emoji
+        emoji_code: u32,
+    },
+
+    /// Remove a reaction from a community message
+    ///
+    /// The reaction can still be removed after the message is deleted.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The user account (receives the reaction rent)
+    /// 1. `[]` The community account
+    /// 2. `[writable]` The message account (may be closed)
+    /// 3. `[writable]` The reaction account
+    RemoveReaction {
+        /// Unicode code point of the emoji
+        emoji_code: u32,
+    },
+
+    /// Upgrade an NFT data record to the current account layout
+    ///
+    /// Anyone may migrate a record; the payer funds any extra rent.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The payer account
+    /// 1. `[writable]` The NFT data account (PDA of `["nft_data", mint]`)
+    /// 2. `[]` The NFT mint account
+    /// 3. `[]` The system program
+    MigrateNFTData,
+
+    /// Set the per-rarity supply caps for an anime (admin only)
+    ///
+    /// Creates the rarity supply account on first use. NFTs of an anime can only be minted
+    /// once its caps have been set.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The admin account (payer)
+    /// 1. `[]` The program config account
+    /// 2. `[writable]` The rarity supply account (PDA of `["rarity_supply", hash(anime_title)]`)
+    /// 3. `[]` The system program
+    SetRaritySupplyCaps {
+        anime_title: String,
+        /// Cap per tier, from Common to Legendary (None leaves the tier uncapped)
+        caps: [Option<u64>; NFTRarity::COUNT],
+    },
+
+    /// Create a collection NFT and its collection record
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The authority account (payer, receives the collection NFT)
+    /// 1. `[signer, writable]` The collection mint account
+    /// 2. `[writable]` The authority's collection token account
+    /// 3. `[writable]` The collection metadata account
+    /// 4. `[writable]` The collection master edition account
+    /// 5. `[writable]` The collection account (PDA of `["collection", collection_mint]`)
+    /// 6. `[]` The system program
+    /// 7. `[]` The token program
+    /// 8. `[]` The associated token program
+    /// 9. `[]` The token metadata program
+    /// 10. `[]` The rent sysvar
+    CreateCollection {
+        name: String,
+        symbol: String,
+        uri: String,
+    },
+
+    /// Remove and replace NFT attributes by trait type
+    ///
+    /// Removals are applied before the new attributes are set. The token metadata is
+    /// updated too, pointing it at `uri` if given.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The metadata update authority (payer if the data grows)
+    /// 1. `[writable]` The NFT data account
+    /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The NFT metadata account
+    /// 4. `[]` The token metadata program
+    /// 5. `[]` The system program
+    EditNFTAttributes {
+        /// Trait types of the attributes to remove
+        remove: Vec<String>,
+        /// Attributes to add, replacing any with the same trait type
+        set: Vec<NFTAttribute>,
+        /// New URI of the metadata JSON carrying the edited attributes
+        uri: Option<String>,
+    },
+
+    /// Offer lamports for an NFT, listed or not, holding them in an offer account
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer account (payer)
+    /// 1. `[]` The NFT mint account
+    /// 2. `[]` The NFT data account
+    /// 3. `[writable]` The offer account (PDA of `["offer", mint, buyer]`)
+    /// 4. `[]` The system program
+    MakeOffer {
+        /// Offered price in lamports
+        price: u64,
+        /// Unix timestamp after which the offer can no longer be accepted
+        expires_at: i64,
+    },
+
+    /// Accept an offer, trading the NFT for the offered lamports
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The NFT owner account (seller)
+    /// 1. `[writable]` The buyer account (receives the offer account's rent)
+    /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The NFT data account
+    /// 4. `[writable]` The offer account
+    /// 5. `[writable]` The owner's NFT token account (unused if the NFT is listed)
+    /// 6. `[writable]` The escrow NFT token account (used if the NFT is listed)
+    /// 7. `[]` The escrow authority
+    /// 8. `[writable]` The buyer's NFT token account
+    /// 9. `[]` The program config account
+    /// 10. `[writable]` The treasury account (platform fee receiver)
+    /// 11. `[]` The token program
+    /// 12.. `[writable]` One account per NFT creator, in the NFT data's order (royalty receivers)
+    AcceptOffer {
+        /// Price in lamports, must match the offer
+        price: u64,
+    },
+
+    /// Cancel an offer, or reclaim an expired one, refunding the buyer
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The buyer account
+    /// 1. `[]` The NFT mint account
+    /// 2. `[writable]` The offer account
+    CancelOffer,
+
+    /// Put an NFT up for English auction, moving it into escrow
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The seller account (payer)
+    /// 1. `[writable]` The NFT data account
+    /// 2. `[]` The NFT mint account
+    /// 3. `[writable]` The seller's NFT token account
+    /// 4. `[writable]` The escrow NFT token account (ATA of the escrow authority)
+    /// 5. `[]` The escrow authority (PDA of `["escrow", mint]`)
+    /// 6. `[writable]` The auction account (PDA of `["auction", mint]`)
+    /// 7. `[]` The system program
+    /// 8. `[]` The token program
+    /// 9. `[]` The associated token program
+    CreateAuction {
+        /// Minimum first bid in lamports
+        reserve_price: u64,
+        /// Minimum amount in lamports each bid must add to the highest bid
+        min_bid_increment: u64,
+        start_at: i64,
+        end_at: i64,
+        /// Anti-sniping window: a bid this close to the end extends it by this many seconds
+        extension_seconds: i64,
+    },
+
+    /// Bid on an auction, escrowing the bid and refunding the previous highest bidder
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The bidder account
+    /// 1. `[]` The NFT mint account
+    /// 2. `[writable]` The auction account
+    /// 3. `[writable]` The previous highest bidder (any account if there is none)
+    /// 4. `[]` The system program
+    PlaceBid {
+        /// Bid in lamports
+        amount: u64,
+    },
+
+    /// Settle an ended auction, delivering the NFT to the winner and paying out,
+    /// or returning the NFT to the seller if there were no bids
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The seller account (receives the proceeds and rent)
+    /// 1. `[]` The NFT mint account
+    /// 2. `[writable]` The NFT data account
+    /// 3. `[writable]` The auction account
+    /// 4. `[writable]` The escrow NFT token account
+    /// 5. `[]` The escrow authority
+    /// 6. `[writable]` The winner's NFT token account (the seller's if there were no bids)
+    /// 7. `[]` The program config account
+    /// 8. `[writable]` The treasury account (platform fee receiver)
+    /// 9. `[]` The token program
+    /// 10.. `[writable]` One account per NFT creator, in the NFT data's order (royalty receivers)
+    SettleAuction,
+
+    /// Accept an SPL token mint as marketplace payment
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The super admin account (payer if the config grows)
+    /// 1. `[writable]` The program config account
+    /// 2. `[]` The system program
+    AddPaymentMint {
+        /// The mint to accept
+        mint: Pubkey,
+    },
+
+    /// Stop accepting an SPL token mint as marketplace payment
+    ///
+    /// Existing listings priced in the mint can still be bought.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The super admin account
+    /// 1. `[writable]` The program config account
+    RemovePaymentMint {
+        /// The mint to stop accepting
+        mint: Pubkey,
+    },
+
+    /// Turn an NFT into a master edition that numbered prints can be minted from
+    ///
+    /// The master edition takes over the mint and freeze authorities of the mint.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The metadata update authority (payer)
+    /// 1. `[signer]` The mint authority
+    /// 2. `[writable]` The NFT mint account
+    /// 3. `[writable]` The NFT data account
+    /// 4. `[]` The NFT metadata account
+    /// 5. `[writable]` The master edition account
+    /// 6. `[]` The system program
+    /// 7. `[]` The token program
+    /// 8. `[]` The token metadata program
+    /// 9. `[]` The rent sysvar
+    CreateMasterEdition {
+        /// Maximum number of prints (None if unlimited)
+        max_supply: Option<u64>,
+    },
+
+    /// Mint a numbered print of a master edition to the master edition's holder
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The master edition holder (payer, receives the print)
+    /// 1. `[signer, writable]` The print mint account
+    /// 2. `[writable]` The holder's print token account
+    /// 3. `[writable]` The print metadata account
+    /// 4. `[writable]` The print edition account
+    /// 5. `[writable]` The print NFT data account (PDA of `["nft_data", print_mint]`)
+    /// 6. `[]` The master mint account
+    /// 7. `[]` The master NFT data account
+    /// 8. `[]` The master metadata account
+    /// 9. `[writable]` The master edition account
+    /// 10. `[writable]` The edition marker account for the print number
+    /// 11. `[]` The holder's master token account
+    /// 12. `[]` The master metadata's update authority (the print's update authority)
+    /// 13. `[]` The system program
+    /// 14. `[]` The token program
+    /// 15. `[]` The associated token program
+    /// 16. `[]` The token metadata program
+    /// 17. `[]` The rent sysvar
+    /// 18. `[writable]` The rarity supply account of the master's anime
+    ///     (PDA of `["rarity_supply", hash(anime_title)]`)
+    PrintEdition {
+        /// The print number, unique and at most the master edition's max supply
+        edition: u64,
+    },
+
+    /// Burn an NFT, close its NFT data and refund the rent to the owner
+    ///
+    /// Every NFT is burned through the token metadata program, which also closes its
+    /// metadata and edition accounts. NFTs minted before master editions were created at
+    /// mint get one first, so their metadata update authority (and mint authority, unless
+    /// soulbound) must co-sign. Soulbound tokens are thawed first, and burning a collection
+    /// item shrinks the collection.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The owner account (receives the rent)
+    /// 1. `[writable]` The NFT mint account
+    /// 2. `[writable]` The owner's NFT token account
+    /// 3. `[writable]` The NFT data account
+    /// 4. `[writable]` The NFT metadata account
+    /// 5. `[writable]` The NFT edition account
+    /// 6. `[writable]` The redemption receipt account (PDA of `["redemption", mint, owner]`)
+    /// 7. `[]` The system program
+    /// 8. `[]` The token program
+    /// 9. `[]` The token metadata program
+    /// 10. `[]` The freeze authority account (PDA of `["soulbound", mint]`)
+    ///
+    /// For an NFT in a collection:
+    /// 11. `[writable]` The collection account (PDA of `["collection", collection mint]`)
+    /// 12. `[writable]` The collection metadata account
+    ///
+    /// Then, after any collection accounts, for a print:
+    /// - `[]` The master mint account
+    /// - `[]` A token account of the master mint
+    /// - `[writable]` The master edition account
+    /// - `[writable]` The edition marker account for the print number
+    ///
+    /// Or for an NFT without an edition:
+    /// - `[signer]` The metadata update authority
+    /// - `[signer]` The mint authority (omitted if soulbound)
+    BurnNFT {
+        /// Record a redemption receipt for the burned NFT (burn-to-redeem)
+        redeem: bool,
+    },
+
+    /// Move a soulbound NFT to a holder's new wallet (admin only)
+    ///
+    /// The old token account is thawed, burned and closed by the freeze authority, which
+    /// soulbound mints make its delegate and close authority, so the holder does not sign.
+    /// The NFT is then minted into the new wallet and frozen there. If the new wallet signs,
+    /// the freeze authority is made the delegate and close authority of its token account
+    /// too, so the NFT can be reissued again.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The admin account (payer for the new token account)
+    /// 1. `[writable]` The current holder (receives the old token account's rent)
+    /// 2. `[]` The holder's new wallet (optionally a signer)
+    /// 3. `[writable]` The NFT mint account
+    /// 4. `[writable]` The NFT data account
+    /// 5. `[writable]` The current holder's NFT token account
+    /// 6. `[writable]` The new wallet's NFT token account (created if missing)
+    /// 7. `[]` The freeze and mint authority (PDA of `["soulbound", mint]`)
+    /// 8. `[]` The program config account
+    /// 9. `[]` The system program
+    /// 10. `[]` The token program
+    /// 11. `[]` The associated token program
+    ReissueSoulboundNFT,
+
+    /// Upgrade a config or community account to the current account layout
+    ///
+    /// Anyone may migrate an account; the payer funds any extra rent. NFT data is
+    /// migrated with MigrateNFTData.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer, writable]` The payer account
+    /// 1. `[writable]` The account to migrate
+    /// 2. `[]` The system program
+    MigrateAccount {
+        /// The type of record the account holds
+        account_type: LegacyAccountType,
     },
 }
 
+/// Marks instruction data as using the versioned wire format
+///
+/// Versioned data is `[VERSIONED_INSTRUCTION_TAG, version, discriminator, ..fields]`
+/// with the fields Borsh-encoded. Any other first byte is decoded with the legacy
+/// layout, where it is the Borsh enum ordinal of the instruction. Fields added to
+/// an existing instruction go after its original fields and may be omitted.
+pub const VERSIONED_INSTRUCTION_TAG: u8 = 0xFF;
+
+/// Current version of the instruction wire format
+pub const INSTRUCTION_VERSION: u8 = 1;
+
+/// Number of instructions that existed when the legacy layout was frozen
+pub const LEGACY_INSTRUCTION_COUNT: u8 = 27;
+
+/// Explicit instruction discriminators for the versioned wire format
+///
+/// These are part of the program's public interface. Never change or reuse a
+/// value; give new instructions the next free one.
+pub mod discriminator {
+    pub const MINT_NFT_REWARD: u8 = 0;
+    pub const MINT_ENHANCED_NFT: u8 = 1;
+    pub const PURCHASE_NFT: u8 = 2;
+    pub const UPDATE_NFT_METADATA: u8 = 3;
+    pub const VERIFY_NFT: u8 = 4;
+    pub const LIST_NFT_FOR_SALE: u8 = 5;
+    pub const CANCEL_NFT_LISTING: u8 = 6;
+    pub const SEND_COMMUNITY_MESSAGE: u8 = 7;
+    pub const INITIALIZE_CONFIG: u8 = 8;
+    pub const UPDATE_CONFIG: u8 = 9;
+    pub const UNVERIFY_NFT: u8 = 10;
+    pub const ADD_ADMIN: u8 = 11;
+    pub const REMOVE_ADMIN: u8 = 12;
+    pub const CREATE_COMMUNITY: u8 = 13;
+    pub const UPDATE_COMMUNITY: u8 = 14;
+    pub const CLOSE_COMMUNITY: u8 = 15;
+    pub const JOIN_COMMUNITY: u8 = 16;
+    pub const LEAVE_COMMUNITY: u8 = 17;
+    pub const ADD_MODERATOR: u8 = 18;
+    pub const REMOVE_MODERATOR: u8 = 19;
+    pub const TRANSFER_COMMUNITY_OWNERSHIP: u8 = 20;
+    pub const SET_MESSAGE_HIDDEN: u8 = 21;
+    pub const DELETE_MESSAGE: u8 = 22;
+    pub const BAN_MEMBER: u8 = 23;
+    pub const UNBAN_MEMBER: u8 = 24;
+    pub const ADD_REACTION: u8 = 25;
+    pub const REMOVE_REACTION: u8 = 26;
+    pub const MIGRATE_NFT_DATA: u8 = 27;
+    pub const SET_RARITY_SUPPLY_CAPS: u8 = 28;
+    pub const CREATE_COLLECTION: u8 = 29;
+    pub const EDIT_NFT_ATTRIBUTES: u8 = 30;
+    pub const MAKE_OFFER: u8 = 31;
+    pub const ACCEPT_OFFER: u8 = 32;
+    pub const CANCEL_OFFER: u8 = 33;
+    pub const CREATE_AUCTION: u8 = 34;
+    pub const PLACE_BID: u8 = 35;
+    pub const SETTLE_AUCTION: u8 = 36;
+    pub const ADD_PAYMENT_MINT: u8 = 37;
+    pub const REMOVE_PAYMENT_MINT: u8 = 38;
+    pub const CREATE_MASTER_EDITION: u8 = 39;
+    pub const PRINT_EDITION: u8 = 40;
+    pub const BURN_NFT: u8 = 41;
+    pub const REISSUE_SOULBOUND_NFT: u8 = 42;
+    pub const MIGRATE_ACCOUNT: u8 = 43;
+}
+
+/// Borsh-decode a value from the front of `data`, advancing past it
+fn read<T: BorshDeserialize>(data: &mut &[u8]) -> Result<T, ProgramError> {
+    T::deserialize(data).map_err(|_| OtakuVerseError::InvalidInstructionData.into())
+}
+
+/// Borsh-decode a field appended to an existing instruction, defaulting it when
+/// older clients leave it out
+fn read_trailing<T: BorshDeserialize + Default>(data: &mut &[u8]) -> Result<T, ProgramError> {
+    if data.is_empty() {
+        return Ok(T::default());
+    }
+    read(data)
+}
+
+impl OtakuVerseInstruction {
+    /// Decode instruction data in either the versioned or the legacy layout
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(OtakuVerseError::InvalidInstructionData)?;
+
+        if tag != VERSIONED_INSTRUCTION_TAG {
+            if tag >= LEGACY_INSTRUCTION_COUNT {
+                return Err(OtakuVerseError::UnknownInstruction.into());
+            }
+            // Legacy ordinals coincide with the discriminators of the same instructions
+            let mut data = rest;
+            let instruction = Self::unpack_fields(tag, &mut data)?;
+            if !data.is_empty() {
+                return Err(OtakuVerseError::InvalidInstructionData.into());
+            }
+            return Ok(instruction);
+        }
+
+        let (&version, rest) = rest
+            .split_first()
+            .ok_or(OtakuVerseError::InvalidInstructionData)?;
+        if version != INSTRUCTION_VERSION {
+            return Err(OtakuVerseError::UnsupportedInstructionVersion.into());
+        }
+
+        let (&discriminator, mut data) = rest
+            .split_first()
+            .ok_or(OtakuVerseError::InvalidInstructionData)?;
+        let instruction = Self::unpack_fields(discriminator, &mut data)?;
+        if !data.is_empty() {
+            return Err(OtakuVerseError::InvalidInstructionData.into());
+        }
+
+        Ok(instruction)
+    }
+
+    /// Decode the Borsh-encoded fields of a versioned instruction
+    fn unpack_fields(discriminator: u8, data: &mut &[u8]) -> Result<Self, ProgramError> {
+        Ok(match discriminator {
+            discriminator::MINT_NFT_REWARD => {
+                let (name, symbol, uri, anime_title, rarity) = read(data)?;
+                let soulbound = read_trailing(data)?;
+                Self::MintNFTReward { name, symbol, uri, anime_title, rarity, soulbound }
+            }
+            discriminator::MINT_ENHANCED_NFT => {
+                let (
+                    name,
+                    symbol,
+                    uri,
+                    anime_title,
+                    rarity,
+                    description,
+                    collection_id,
+                    attributes,
+                    royalty_basis_points,
+                ) = read(data)?;
+                let creators = read_trailing(data)?;
+                let typed_attributes = read_trailing(data)?;
+                // Older clients expect no prints, which is not the decoded default
+                let max_supply = if data.is_empty() { Some(0) } else { read(data)? };
+                Self::MintEnhancedNFT {
+                    name,
+                    symbol,
+                    uri,
+                    anime_title,
+                    rarity,
+                    description,
+                    collection_id,
+                    attributes,
+                    royalty_basis_points,
+                    creators,
+                    typed_attributes,
+                    max_supply,
+                }
+            }
+            discriminator::PURCHASE_NFT => {
+                let price = read(data)?;
+                Self::PurchaseNFT { price }
+            }
+            discriminator::UPDATE_NFT_METADATA => {
+                let (uri, name, description, attributes_to_add, royalty_basis_points) = read(data)?;
+                let typed_attributes_to_add = read_trailing(data)?;
+                Self::UpdateNFTMetadata {
+                    uri,
+                    name,
+                    description,
+                    attributes_to_add,
+                    royalty_basis_points,
+                    typed_attributes_to_add,
+                }
+            }
+            discriminator::VERIFY_NFT => {
+                let mint = read(data)?;
+                Self::VerifyNFT { mint }
+            }
+            discriminator::LIST_NFT_FOR_SALE => {
+                let price = read(data)?;
+                let listing_type = read_trailing(data)?;
+                let payment_mint = read_trailing(data)?;
+                Self::ListNFTForSale { price, listing_type, payment_mint }
+            }
+            discriminator::CANCEL_NFT_LISTING => Self::CancelNFTListing,
+            discriminator::SEND_COMMUNITY_MESSAGE => {
+                let (community_id, content, reply_to) = read(data)?;
+                Self::SendCommunityMessage { community_id, content, reply_to }
+            }
+            discriminator::INITIALIZE_CONFIG => {
+                let (treasury, platform_fee_basis_points) = read(data)?;
+                Self::InitializeConfig { treasury, platform_fee_basis_points }
+            }
+            discriminator::UPDATE_CONFIG => {
+                let (treasury, platform_fee_basis_points) = read(data)?;
+                Self::UpdateConfig { treasury, platform_fee_basis_points }
+            }
+            discriminator::UNVERIFY_NFT => {
+                let mint = read(data)?;
+                Self::UnverifyNFT { mint }
+            }
+            discriminator::ADD_ADMIN => {
+                let admin = read(data)?;
+                Self::AddAdmin { admin }
+            }
+            discriminator::REMOVE_ADMIN => {
+                let admin = read(data)?;
+                Self::RemoveAdmin { admin }
+            }
+            discriminator::CREATE_COMMUNITY => {
+                let (id, name, description) = read(data)?;
+                Self::CreateCommunity { id, name, description }
+            }
+            discriminator::UPDATE_COMMUNITY => {
+                let (name, description) = read(data)?;
+                Self::UpdateCommunity { name, description }
+            }
+            discriminator::CLOSE_COMMUNITY => Self::CloseCommunity,
+            discriminator::JOIN_COMMUNITY => Self::JoinCommunity,
+            discriminator::LEAVE_COMMUNITY => Self::LeaveCommunity,
+            discriminator::ADD_MODERATOR => {
+                let moderator = read(data)?;
+                Self::AddModerator { moderator }
+            }
+            discriminator::REMOVE_MODERATOR => {
+                let moderator = read(data)?;
+                Self::RemoveModerator { moderator }
+            }
+            discriminator::TRANSFER_COMMUNITY_OWNERSHIP => {
+                let new_creator = read(data)?;
+                Self::TransferCommunityOwnership { new_creator }
+            }
+            discriminator::SET_MESSAGE_HIDDEN => {
+                let hidden = read(data)?;
+                Self::SetMessageHidden { hidden }
+            }
+            discriminator::DELETE_MESSAGE => Self::DeleteMessage,
+            discriminator::BAN_MEMBER => {
+                let user = read(data)?;
+                Self::BanMember { user }
+            }
+            discriminator::UNBAN_MEMBER => {
+                let user = read(data)?;
+                Self::UnbanMember { user }
+            }
+            discriminator::ADD_REACTION => {
+                let emoji_code = read(data)?;
+                Self::AddReaction { emoji_code }
+            }
+            discriminator::REMOVE_REACTION => {
+                let emoji_code = read(data)?;
+                Self::RemoveReaction { emoji_code }
+            }
+            discriminator::MIGRATE_NFT_DATA => Self::MigrateNFTData,
+            discriminator::SET_RARITY_SUPPLY_CAPS => {
+                let (anime_title, caps) = read(data)?;
+                Self::SetRaritySupplyCaps { anime_title, caps }
+            }
+            discriminator::CREATE_COLLECTION => {
+                let (name, symbol, uri) = read(data)?;
+                Self::CreateCollection { name, symbol, uri }
+            }
+            discriminator::EDIT_NFT_ATTRIBUTES => {
+                let (remove, set, uri) = read(data)?;
+                Self::EditNFTAttributes { remove, set, uri }
+            }
+            discriminator::MAKE_OFFER => {
+                let (price, expires_at) = read(data)?;
+                Self::MakeOffer { price, expires_at }
+            }
+            discriminator::ACCEPT_OFFER => {
+                let price = read(data)?;
+                Self::AcceptOffer { price }
+            }
+            discriminator::CANCEL_OFFER => Self::CancelOffer,
+            discriminator::CREATE_AUCTION => {
+                let (
+                    reserve_price,
+                    min_bid_increment,
+                    start_at,
+                    end_at,
+                    extension_seconds,
+                ) = read(data)?;
+                Self::CreateAuction {
+                    reserve_price,
+                    min_bid_increment,
+                    start_at,
+                    end_at,
+                    extension_seconds,
+                }
+            }
+            discriminator::PLACE_BID => {
+                let amount = read(data)?;
+                Self::PlaceBid { amount }
+            }
+            discriminator::SETTLE_AUCTION => Self::SettleAuction,
+            discriminator::ADD_PAYMENT_MINT => {
+                let mint = read(data)?;
+                Self::AddPaymentMint { mint }
+            }
+            discriminator::REMOVE_PAYMENT_MINT => {
+                let mint = read(data)?;
+                Self::RemovePaymentMint { mint }
+            }
+            discriminator::CREATE_MASTER_EDITION => {
+                let max_supply = read(data)?;
+                Self::CreateMasterEdition { max_supply }
+            }
+            discriminator::PRINT_EDITION => {
+                let edition = read(data)?;
+                Self::PrintEdition { edition }
+            }
+            discriminator::BURN_NFT => {
+                let redeem = read(data)?;
+                Self::BurnNFT { redeem }
+            }
+            discriminator::REISSUE_SOULBOUND_NFT => Self::ReissueSoulboundNFT,
+            discriminator::MIGRATE_ACCOUNT => {
+                let account_type = read(data)?;
+                Self::MigrateAccount { account_type }
+            }
+            _ => return Err(OtakuVerseError::UnknownInstruction.into()),
+        })
+    }
+
+    /// Encode the instruction in the current versioned layout
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION];
+        let result = match self {
+            Self::MintNFTReward { name, symbol, uri, anime_title, rarity, soulbound } => {
+                buf.push(discriminator::MINT_NFT_REWARD);
+                (name, symbol, uri, anime_title, rarity, soulbound).serialize(&mut buf)
+            }
+            Self::MintEnhancedNFT {
+                name,
+                symbol,
+                uri,
+                anime_title,
+                rarity,
+                description,
+                collection_id,
+                attributes,
+                royalty_basis_points,
+                creators,
+                typed_attributes,
+                max_supply,
+            } => {
+                buf.push(discriminator::MINT_ENHANCED_NFT);
+                (
+                    name,
+                    symbol,
+                    uri,
+                    anime_title,
+                    rarity,
+                    description,
+                    collection_id,
+                    attributes,
+                    royalty_basis_points,
+                    creators,
+                    typed_attributes,
+                    max_supply,
+                ).serialize(&mut buf)
+            }
+            Self::PurchaseNFT { price } => {
+                buf.push(discriminator::PURCHASE_NFT);
+                price.serialize(&mut buf)
+            }
+            Self::UpdateNFTMetadata {
+                uri,
+                name,
+                description,
+                attributes_to_add,
+                royalty_basis_points,
+                typed_attributes_to_add,
+            } => {
+                buf.push(discriminator::UPDATE_NFT_METADATA);
+                (
+                    uri,
+                    name,
+                    description,
+                    attributes_to_add,
+                    royalty_basis_points,
+                    typed_attributes_to_add,
+                ).serialize(&mut buf)
+            }
+            Self::VerifyNFT { mint } => {
+                buf.push(discriminator::VERIFY_NFT);
+                mint.serialize(&mut buf)
+            }
+            Self::ListNFTForSale { price, listing_type, payment_mint } => {
+                buf.push(discriminator::LIST_NFT_FOR_SALE);
+                (price, listing_type, payment_mint).serialize(&mut buf)
+            }
+            Self::CancelNFTListing => {
+                buf.push(discriminator::CANCEL_NFT_LISTING);
+                Ok(())
+            }
+            Self::SendCommunityMessage { community_id, content, reply_to } => {
+                buf.push(discriminator::SEND_COMMUNITY_MESSAGE);
+                (community_id, content, reply_to).serialize(&mut buf)
+            }
+            Self::InitializeConfig { treasury, platform_fee_basis_points } => {
+                buf.push(discriminator::INITIALIZE_CONFIG);
+                (treasury, platform_fee_basis_points).serialize(&mut buf)
+            }
+            Self::UpdateConfig { treasury, platform_fee_basis_points } => {
+                buf.push(discriminator::UPDATE_CONFIG);
+                (treasury, platform_fee_basis_points).serialize(&mut buf)
+            }
+            Self::UnverifyNFT { mint } => {
+                buf.push(discriminator::UNVERIFY_NFT);
+                mint.serialize(&mut buf)
+            }
+            Self::AddAdmin { admin } => {
+                buf.push(discriminator::ADD_ADMIN);
+                admin.serialize(&mut buf)
+            }
+            Self::RemoveAdmin { admin } => {
+                buf.push(discriminator::REMOVE_ADMIN);
+                admin.serialize(&mut buf)
+            }
+            Self::CreateCommunity { id, name, description } => {
+                buf.push(discriminator::CREATE_COMMUNITY);
+                (id, name, description).serialize(&mut buf)
+            }
+            Self::UpdateCommunity { name, description } => {
+                buf.push(discriminator::UPDATE_COMMUNITY);
+                (name, description).serialize(&mut buf)
+            }
+            Self::CloseCommunity => {
+                buf.push(discriminator::CLOSE_COMMUNITY);
+                Ok(())
+            }
+            Self::JoinCommunity => {
+                buf.push(discriminator::JOIN_COMMUNITY);
+                Ok(())
+            }
+            Self::LeaveCommunity => {
+                buf.push(discriminator::LEAVE_COMMUNITY);
+                Ok(())
+            }
+            Self::AddModerator { moderator } => {
+                buf.push(discriminator::ADD_MODERATOR);
+                moderator.serialize(&mut buf)
+            }
+            Self::RemoveModerator { moderator } => {
+                buf.push(discriminator::REMOVE_MODERATOR);
+                moderator.serialize(&mut buf)
+            }
+            Self::TransferCommunityOwnership { new_creator } => {
+                buf.push(discriminator::TRANSFER_COMMUNITY_OWNERSHIP);
+                new_creator.serialize(&mut buf)
+            }
+            Self::SetMessageHidden { hidden } => {
+                buf.push(discriminator::SET_MESSAGE_HIDDEN);
+                hidden.serialize(&mut buf)
+            }
+            Self::DeleteMessage => {
+                buf.push(discriminator::DELETE_MESSAGE);
+                Ok(())
+            }
+            Self::BanMember { user } => {
+                buf.push(discriminator::BAN_MEMBER);
+                user.serialize(&mut buf)
+            }
+            Self::UnbanMember { user } => {
+                buf.push(discriminator::UNBAN_MEMBER);
+                user.serialize(&mut buf)
+            }
+            Self::AddReaction { emoji_code } => {
+                buf.push(discriminator::ADD_REACTION);
+                emoji_code.serialize(&mut buf)
+            }
+            Self::RemoveReaction { emoji_code } => {
+                buf.push(discriminator::REMOVE_REACTION);
+                emoji_code.serialize(&mut buf)
+            }
+            Self::MigrateNFTData => {
+                buf.push(discriminator::MIGRATE_NFT_DATA);
+                Ok(())
+            }
+            Self::SetRaritySupplyCaps { anime_title, caps } => {
+                buf.push(discriminator::SET_RARITY_SUPPLY_CAPS);
+                (anime_title, caps).serialize(&mut buf)
+            }
+            Self::CreateCollection { name, symbol, uri } => {
+                buf.push(discriminator::CREATE_COLLECTION);
+                (name, symbol, uri).serialize(&mut buf)
+            }
+            Self::EditNFTAttributes { remove, set, uri } => {
+                buf.push(discriminator::EDIT_NFT_ATTRIBUTES);
+                (remove, set, uri).serialize(&mut buf)
+            }
+            Self::MakeOffer { price, expires_at } => {
+                buf.push(discriminator::MAKE_OFFER);
+                (price, expires_at).serialize(&mut buf)
+            }
+            Self::AcceptOffer { price } => {
+                buf.push(discriminator::ACCEPT_OFFER);
+                price.serialize(&mut buf)
+            }
+            Self::CancelOffer => {
+                buf.push(discriminator::CANCEL_OFFER);
+                Ok(())
+            }
+            Self::CreateAuction {
+                reserve_price,
+                min_bid_increment,
+                start_at,
+                end_at,
+                extension_seconds,
+            } => {
+                buf.push(discriminator::CREATE_AUCTION);
+                (
+                    reserve_price,
+                    min_bid_increment,
+                    start_at,
+                    end_at,
+                    extension_seconds,
+                ).serialize(&mut buf)
+            }
+            Self::PlaceBid { amount } => {
+                buf.push(discriminator::PLACE_BID);
+                amount.serialize(&mut buf)
+            }
+            Self::SettleAuction => {
+                buf.push(discriminator::SETTLE_AUCTION);
+                Ok(())
+            }
+            Self::AddPaymentMint { mint } => {
+                buf.push(discriminator::ADD_PAYMENT_MINT);
+                mint.serialize(&mut buf)
+            }
+            Self::RemovePaymentMint { mint } => {
+                buf.push(discriminator::REMOVE_PAYMENT_MINT);
+                mint.serialize(&mut buf)
+            }
+            Self::CreateMasterEdition { max_supply } => {
+                buf.push(discriminator::CREATE_MASTER_EDITION);
+                max_supply.serialize(&mut buf)
+            }
+            Self::PrintEdition { edition } => {
+                buf.push(discriminator::PRINT_EDITION);
+                edition.serialize(&mut buf)
+            }
+            Self::BurnNFT { redeem } => {
+                buf.push(discriminator::BURN_NFT);
+                redeem.serialize(&mut buf)
+            }
+            Self::ReissueSoulboundNFT => {
+                buf.push(discriminator::REISSUE_SOULBOUND_NFT);
+                Ok(())
+            }
+            Self::MigrateAccount { account_type } => {
+                buf.push(discriminator::MIGRATE_ACCOUNT);
+                account_type.serialize(&mut buf)
+            }
+        };
+        result.expect("serializing into a Vec cannot fail");
+        buf
+    }
+}
+
 /// Creates a MintNFTReward instruction
+///
+/// Soulbound rewards are minted by their freeze authority, so `mint_authority` is ignored.
 pub fn mint_nft_reward(
     program_id: &Pubkey,
     authority: &Pubkey,
@@ -147,19 +1339,32 @@ pub fn mint_nft_reward(
     uri: String,
     anime_title: String,
     rarity: String,
+    soulbound: bool,
 ) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new_readonly(*authority, true),
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (rarity_supply, _) = RaritySupplyData::find_address(program_id, &anime_title);
+    let (freeze_authority, _) = NFTData::find_freeze_authority(program_id, nft_mint);
+    let mint_authority = if soulbound { &freeze_authority } else { mint_authority };
+    let mut accounts = vec![
+        AccountMeta::new(*authority, true),
         AccountMeta::new(*nft_mint, false),
         AccountMeta::new(*nft_token, false),
         AccountMeta::new(*nft_metadata, false),
         AccountMeta::new_readonly(*mint_authority, false),
-        AccountMeta::new_readonly(*update_authority, false),
+        AccountMeta::new_readonly(*update_authority, !soulbound),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(mpl_token_metadata::id(), false),
         AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(rarity_supply, false),
     ];
+    if soulbound {
+        accounts.push(AccountMeta::new_readonly(freeze_authority, false));
+    } else {
+        let (master_edition, _) = find_master_edition_account(nft_mint);
+        accounts.push(AccountMeta::new(master_edition, false));
+    }
 
     let instruction_data = OtakuVerseInstruction::MintNFTReward {
         name,
@@ -167,12 +1372,13 @@ pub fn mint_nft_reward(
         uri,
         anime_title,
         rarity,
+        soulbound,
     };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
@@ -183,25 +1389,46 @@ pub fn purchase_nft(
     seller: &Pubkey,
     nft_mint: &Pubkey,
     buyer_token: &Pubkey,
-    seller_token: &Pubkey,
+    creators: &[Pubkey],
+    treasury: &Pubkey,
     price: u64,
+    payment_mint: Option<&Pubkey>,
 ) -> Instruction {
-    let accounts = vec![
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let mut accounts = vec![
         AccountMeta::new(*buyer, true),
         AccountMeta::new(*seller, false),
         AccountMeta::new_readonly(*nft_mint, false),
         AccountMeta::new(*buyer_token, false),
-        AccountMeta::new(*seller_token, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*treasury, false),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
     ];
+    match payment_mint {
+        Some(payment_mint) => {
+            let owners = [buyer, seller, treasury].into_iter().chain(creators);
+            accounts.extend(owners.map(|owner| {
+                AccountMeta::new(get_associated_token_address(owner, payment_mint), false)
+            }));
+        }
+        None => {
+            accounts.extend(creators.iter().map(|creator| AccountMeta::new(*creator, false)));
+        }
+    }
 
     let instruction_data = OtakuVerseInstruction::PurchaseNFT { price };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
@@ -221,21 +1448,41 @@ pub fn mint_enhanced_nft(
     rarity: NFTRarity,
     description: String,
     collection_id: Option<Pubkey>,
-    attributes: Vec<NFTAttribute>,
+    attributes: Vec<NFTAttributeV1>,
     royalty_basis_points: u16,
+    creators: Vec<NFTCreator>,
+    typed_attributes: Vec<NFTAttribute>,
+    max_supply: Option<u64>,
 ) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new_readonly(*authority, true),
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (rarity_supply, _) = RaritySupplyData::find_address(program_id, &anime_title);
+    let (master_edition, _) = find_master_edition_account(nft_mint);
+    let mut accounts = vec![
+        AccountMeta::new(*authority, true),
         AccountMeta::new(*nft_mint, false),
         AccountMeta::new(*nft_token, false),
         AccountMeta::new(*nft_metadata, false),
-        AccountMeta::new_readonly(*mint_authority, false),
-        AccountMeta::new_readonly(*update_authority, false),
+        AccountMeta::new_readonly(*mint_authority, true),
+        AccountMeta::new_readonly(*update_authority, true),
         AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(spl_token::id(), false),
         AccountMeta::new_readonly(mpl_token_metadata::id(), false),
         AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(rarity_supply, false),
+        AccountMeta::new(master_edition, false),
     ];
+    if let Some(collection_mint) = collection_id {
+        let (collection, _) = CollectionData::find_address(program_id, &collection_mint);
+        let (collection_metadata, _) = find_metadata_account(&collection_mint);
+        let (collection_master_edition, _) = find_master_edition_account(&collection_mint);
+        accounts.extend([
+            AccountMeta::new(collection, false),
+            AccountMeta::new_readonly(collection_mint, false),
+            AccountMeta::new(collection_metadata, false),
+            AccountMeta::new_readonly(collection_master_edition, false),
+        ]);
+    }
 
     let instruction_data = OtakuVerseInstruction::MintEnhancedNFT {
         name,
@@ -247,31 +1494,39 @@ pub fn mint_enhanced_nft(
         collection_id,
         attributes,
         royalty_basis_points,
+        creators,
+        typed_attributes,
+        max_supply,
     };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
 /// Creates an UpdateNFTMetadata instruction
 pub fn update_nft_metadata(
     program_id: &Pubkey,
-    owner: &Pubkey,
+    update_authority: &Pubkey,
     nft_metadata: &Pubkey,
     nft_mint: &Pubkey,
     uri: Option<String>,
     name: Option<String>,
     description: Option<String>,
-    attributes_to_add: Vec<NFTAttribute>,
+    attributes_to_add: Vec<NFTAttributeV1>,
     royalty_basis_points: Option<u16>,
+    typed_attributes_to_add: Vec<NFTAttribute>,
 ) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
     let accounts = vec![
-        AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new(*update_authority, true),
+        AccountMeta::new(nft_data, false),
         AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
     ];
 
     let instruction_data = OtakuVerseInstruction::UpdateNFTMetadata {
@@ -280,12 +1535,13 @@ pub fn update_nft_metadata(
         description,
         attributes_to_add,
         royalty_basis_points,
+        typed_attributes_to_add,
     };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
@@ -293,45 +1549,87 @@ pub fn update_nft_metadata(
 pub fn verify_nft(
     program_id: &Pubkey,
     authority: &Pubkey,
-    nft_metadata: &Pubkey,
     nft_mint: &Pubkey,
-    mint: Pubkey,
 ) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
     let accounts = vec![
         AccountMeta::new_readonly(*authority, true),
-        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new(nft_data, false),
         AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(config, false),
     ];
 
-    let instruction_data = OtakuVerseInstruction::VerifyNFT { mint };
+    let instruction_data = OtakuVerseInstruction::VerifyNFT { mint: *nft_mint };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
-/// Creates a ListNFTForSale instruction
-pub fn list_nft_for_sale(
+/// Creates an UnverifyNFT instruction
+pub fn unverify_nft(
     program_id: &Pubkey,
-    owner: &Pubkey,
-    nft_metadata: &Pubkey,
+    authority: &Pubkey,
     nft_mint: &Pubkey,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(config, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::UnverifyNFT { mint: *nft_mint };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a ListNFTForSale instruction
+pub fn list_nft_for_sale(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    nft_mint: &Pubkey,
+    owner_token: &Pubkey,
     price: u64,
+    listing_type: ListingType,
+    payment_mint: Option<Pubkey>,
 ) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
     let accounts = vec![
-        AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(nft_data, false),
         AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(config, false),
     ];
 
-    let instruction_data = OtakuVerseInstruction::ListNFTForSale { price };
+    let instruction_data = OtakuVerseInstruction::ListNFTForSale {
+        price,
+        listing_type,
+        payment_mint,
+    };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
@@ -339,13 +1637,20 @@ pub fn list_nft_for_sale(
 pub fn cancel_nft_listing(
     program_id: &Pubkey,
     owner: &Pubkey,
-    nft_metadata: &Pubkey,
     nft_mint: &Pubkey,
+    owner_token: &Pubkey,
 ) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
     let accounts = vec![
-        AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(nft_data, false),
         AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
     ];
 
     let instruction_data = OtakuVerseInstruction::CancelNFTListing;
@@ -353,34 +1658,1162 @@ pub fn cancel_nft_listing(
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
     }
 }
 
 /// Creates a SendCommunityMessage instruction
+///
+/// `message_index` must be the community's current `message_count`.
 pub fn send_community_message(
     program_id: &Pubkey,
     sender: &Pubkey,
-    community: &Pubkey,
-    message: &Pubkey,
     community_id: String,
+    message_index: u64,
     content: String,
+    reply_to: Option<u64>,
 ) -> Instruction {
-    let accounts = vec![
+    let (community, _) = CommunityData::find_address(program_id, &community_id);
+    let (message, _) = MessageData::find_address(program_id, &community, message_index);
+    let (membership, _) = MembershipData::find_address(program_id, &community, sender);
+    let (ban, _) = BanData::find_address(program_id, &community, sender);
+    let mut accounts = vec![
         AccountMeta::new(*sender, true),
-        AccountMeta::new(*community, false),
-        AccountMeta::new(*message, false),
+        AccountMeta::new(community, false),
+        AccountMeta::new(message, false),
         AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(membership, false),
+        AccountMeta::new_readonly(ban, false),
     ];
+    if let Some(parent_index) = reply_to {
+        let (parent, _) = MessageData::find_address(program_id, &community, parent_index);
+        accounts.push(AccountMeta::new(parent, false));
+    }
 
     let instruction_data = OtakuVerseInstruction::SendCommunityMessage {
         community_id,
         content,
+        reply_to,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an InitializeConfig instruction
+pub fn initialize_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    treasury: Pubkey,
+    platform_fee_basis_points: u16,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(config, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(program_data, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::InitializeConfig {
+        treasury,
+        platform_fee_basis_points,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an UpdateConfig instruction
+pub fn update_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    treasury: Option<Pubkey>,
+    platform_fee_basis_points: Option<u16>,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(config, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::UpdateConfig {
+        treasury,
+        platform_fee_basis_points,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an AddAdmin instruction
+pub fn add_admin(program_id: &Pubkey, authority: &Pubkey, admin: Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(config, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::AddAdmin { admin };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a RemoveAdmin instruction
+pub fn remove_admin(program_id: &Pubkey, authority: &Pubkey, admin: Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(config, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::RemoveAdmin { admin };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CreateCommunity instruction
+pub fn create_community(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    id: String,
+    name: String,
+    description: String,
+) -> Instruction {
+    let (community, _) = CommunityData::find_address(program_id, &id);
+    let accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new(community, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CreateCommunity {
+        id,
+        name,
+        description,
     };
 
     Instruction {
         program_id: *program_id,
         accounts,
-        data: instruction_data.try_to_vec().unwrap(),
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an UpdateCommunity instruction
+pub fn update_community(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    community: &Pubkey,
+    name: Option<String>,
+    description: Option<String>,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*creator, true),
+        AccountMeta::new(*community, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::UpdateCommunity { name, description };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CloseCommunity instruction
+pub fn close_community(program_id: &Pubkey, creator: &Pubkey, community: &Pubkey) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new(*community, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CloseCommunity;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a JoinCommunity instruction
+pub fn join_community(program_id: &Pubkey, user: &Pubkey, community: &Pubkey) -> Instruction {
+    let (membership, _) = MembershipData::find_address(program_id, community, user);
+    let (ban, _) = BanData::find_address(program_id, community, user);
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*community, false),
+        AccountMeta::new(membership, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(ban, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::JoinCommunity;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a LeaveCommunity instruction
+pub fn leave_community(program_id: &Pubkey, user: &Pubkey, community: &Pubkey) -> Instruction {
+    let (membership, _) = MembershipData::find_address(program_id, community, user);
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*community, false),
+        AccountMeta::new(membership, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::LeaveCommunity;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an AddModerator instruction
+pub fn add_moderator(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    community: &Pubkey,
+    moderator: Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*creator, true),
+        AccountMeta::new(*community, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::AddModerator { moderator };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a RemoveModerator instruction
+pub fn remove_moderator(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    community: &Pubkey,
+    moderator: Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*creator, true),
+        AccountMeta::new(*community, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::RemoveModerator { moderator };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a TransferCommunityOwnership instruction
+pub fn transfer_community_ownership(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    community: &Pubkey,
+    new_creator: Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*creator, true),
+        AccountMeta::new(*community, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::TransferCommunityOwnership { new_creator };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a SetMessageHidden instruction
+pub fn set_message_hidden(
+    program_id: &Pubkey,
+    moderator: &Pubkey,
+    community: &Pubkey,
+    message: &Pubkey,
+    hidden: bool,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*moderator, true),
+        AccountMeta::new_readonly(*community, false),
+        AccountMeta::new(*message, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::SetMessageHidden { hidden };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a DeleteMessage instruction
+pub fn delete_message(
+    program_id: &Pubkey,
+    moderator: &Pubkey,
+    community: &Pubkey,
+    message: &Pubkey,
+    sender: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*moderator, true),
+        AccountMeta::new_readonly(*community, false),
+        AccountMeta::new(*message, false),
+        AccountMeta::new(*sender, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::DeleteMessage;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a BanMember instruction
+pub fn ban_member(
+    program_id: &Pubkey,
+    moderator: &Pubkey,
+    community: &Pubkey,
+    user: Pubkey,
+) -> Instruction {
+    let (ban, _) = BanData::find_address(program_id, community, &user);
+    let (membership, _) = MembershipData::find_address(program_id, community, &user);
+    let accounts = vec![
+        AccountMeta::new(*moderator, true),
+        AccountMeta::new(*community, false),
+        AccountMeta::new(ban, false),
+        AccountMeta::new(membership, false),
+        AccountMeta::new(user, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::BanMember { user };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an UnbanMember instruction
+pub fn unban_member(
+    program_id: &Pubkey,
+    moderator: &Pubkey,
+    community: &Pubkey,
+    banned_by: &Pubkey,
+    user: Pubkey,
+) -> Instruction {
+    let (ban, _) = BanData::find_address(program_id, community, &user);
+    let accounts = vec![
+        AccountMeta::new_readonly(*moderator, true),
+        AccountMeta::new_readonly(*community, false),
+        AccountMeta::new(ban, false),
+        AccountMeta::new(*banned_by, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::UnbanMember { user };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an AddReaction instruction
+pub fn add_reaction(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    community: &Pubkey,
+    message: &Pubkey,
+    emoji_code: u32,
+) -> Instruction {
+    let (reaction, _) = ReactionData::find_address(program_id, message, user, emoji_code);
+    let (membership, _) = MembershipData::find_address(program_id, community, user);
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(*community, false),
+        AccountMeta::new(*message, false),
+        AccountMeta::new(reaction, false),
+        AccountMeta::new_readonly(membership, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::AddReaction { emoji_code };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a RemoveReaction instruction
+pub fn remove_reaction(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    community: &Pubkey,
+    message: &Pubkey,
+    emoji_code: u32,
+) -> Instruction {
+    let (reaction, _) = ReactionData::find_address(program_id, message, user, emoji_code);
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(*community, false),
+        AccountMeta::new(*message, false),
+        AccountMeta::new(reaction, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::RemoveReaction { emoji_code };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a MigrateNFTData instruction
+pub fn migrate_nft_data(program_id: &Pubkey, payer: &Pubkey, nft_mint: &Pubkey) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::MigrateNFTData;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a SetRaritySupplyCaps instruction
+pub fn set_rarity_supply_caps(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    anime_title: String,
+    caps: [Option<u64>; NFTRarity::COUNT],
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let (rarity_supply, _) = RaritySupplyData::find_address(program_id, &anime_title);
+    let accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(rarity_supply, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::SetRaritySupplyCaps { anime_title, caps };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CreateCollection instruction
+pub fn create_collection(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    collection_mint: &Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Instruction {
+    let collection_token = get_associated_token_address(authority, collection_mint);
+    let (collection_metadata, _) = find_metadata_account(collection_mint);
+    let (collection_master_edition, _) = find_master_edition_account(collection_mint);
+    let (collection, _) = CollectionData::find_address(program_id, collection_mint);
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*collection_mint, true),
+        AccountMeta::new(collection_token, false),
+        AccountMeta::new(collection_metadata, false),
+        AccountMeta::new(collection_master_edition, false),
+        AccountMeta::new(collection, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(rent::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CreateCollection { name, symbol, uri };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an EditNFTAttributes instruction
+pub fn edit_nft_attributes(
+    program_id: &Pubkey,
+    update_authority: &Pubkey,
+    nft_metadata: &Pubkey,
+    nft_mint: &Pubkey,
+    remove: Vec<String>,
+    set: Vec<NFTAttribute>,
+    uri: Option<String>,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let accounts = vec![
+        AccountMeta::new(*update_authority, true),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*nft_metadata, false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::EditNFTAttributes { remove, set, uri };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a MakeOffer instruction
+pub fn make_offer(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    nft_mint: &Pubkey,
+    price: u64,
+    expires_at: i64,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (offer, _) = OfferData::find_address(program_id, nft_mint, buyer);
+    let accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new_readonly(nft_data, false),
+        AccountMeta::new(offer, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::MakeOffer { price, expires_at };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an AcceptOffer instruction
+pub fn accept_offer(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    buyer: &Pubkey,
+    nft_mint: &Pubkey,
+    owner_token: &Pubkey,
+    buyer_token: &Pubkey,
+    creators: &[Pubkey],
+    treasury: &Pubkey,
+    price: u64,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (offer, _) = OfferData::find_address(program_id, nft_mint, buyer);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*buyer, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(offer, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(*buyer_token, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*treasury, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(creators.iter().map(|creator| AccountMeta::new(*creator, false)));
+
+    let instruction_data = OtakuVerseInstruction::AcceptOffer { price };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CancelOffer instruction
+pub fn cancel_offer(program_id: &Pubkey, buyer: &Pubkey, nft_mint: &Pubkey) -> Instruction {
+    let (offer, _) = OfferData::find_address(program_id, nft_mint, buyer);
+    let accounts = vec![
+        AccountMeta::new(*buyer, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(offer, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CancelOffer;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CreateAuction instruction
+pub fn create_auction(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    seller_token: &Pubkey,
+    reserve_price: u64,
+    min_bid_increment: u64,
+    start_at: i64,
+    end_at: i64,
+    extension_seconds: i64,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
+    let (auction, _) = AuctionData::find_address(program_id, nft_mint);
+    let accounts = vec![
+        AccountMeta::new(*seller, true),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(*seller_token, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CreateAuction {
+        reserve_price,
+        min_bid_increment,
+        start_at,
+        end_at,
+        extension_seconds,
+    };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a PlaceBid instruction
+///
+/// `previous_bidder` is the current highest bidder, or None before the first bid.
+pub fn place_bid(
+    program_id: &Pubkey,
+    bidder: &Pubkey,
+    nft_mint: &Pubkey,
+    previous_bidder: Option<&Pubkey>,
+    amount: u64,
+) -> Instruction {
+    let (auction, _) = AuctionData::find_address(program_id, nft_mint);
+    let accounts = vec![
+        AccountMeta::new(*bidder, true),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new(*previous_bidder.unwrap_or(bidder), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::PlaceBid { amount };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a SettleAuction instruction
+pub fn settle_auction(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    nft_mint: &Pubkey,
+    winner_token: &Pubkey,
+    creators: &[Pubkey],
+    treasury: &Pubkey,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (auction, _) = AuctionData::find_address(program_id, nft_mint);
+    let (escrow_authority, _) = NFTData::find_escrow_authority(program_id, nft_mint);
+    let escrow_token = get_associated_token_address(&escrow_authority, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*seller, false),
+        AccountMeta::new_readonly(*nft_mint, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new(escrow_token, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(*winner_token, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*treasury, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(creators.iter().map(|creator| AccountMeta::new(*creator, false)));
+
+    let instruction_data = OtakuVerseInstruction::SettleAuction;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates an AddPaymentMint instruction
+pub fn add_payment_mint(program_id: &Pubkey, authority: &Pubkey, mint: Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(config, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::AddPaymentMint { mint };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a RemovePaymentMint instruction
+pub fn remove_payment_mint(program_id: &Pubkey, authority: &Pubkey, mint: Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(config, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::RemovePaymentMint { mint };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a CreateMasterEdition instruction
+pub fn create_master_edition(
+    program_id: &Pubkey,
+    update_authority: &Pubkey,
+    mint_authority: &Pubkey,
+    nft_mint: &Pubkey,
+    max_supply: Option<u64>,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (nft_metadata, _) = find_metadata_account(nft_mint);
+    let (master_edition, _) = find_master_edition_account(nft_mint);
+    let accounts = vec![
+        AccountMeta::new(*update_authority, true),
+        AccountMeta::new_readonly(*mint_authority, true),
+        AccountMeta::new(*nft_mint, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new_readonly(nft_metadata, false),
+        AccountMeta::new(master_edition, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(rent::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::CreateMasterEdition { max_supply };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Derive the token metadata edition marker PDA tracking a print number of a master mint
+pub fn find_edition_marker_account(master_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+    let marker_number = (edition / EDITION_MARKER_BIT_SIZE).to_string();
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            master_mint.as_ref(),
+            EDITION.as_bytes(),
+            marker_number.as_bytes(),
+        ],
+        &mpl_token_metadata::id(),
+    )
+}
+
+/// Creates a PrintEdition instruction
+pub fn print_edition(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    print_mint: &Pubkey,
+    master_mint: &Pubkey,
+    master_update_authority: &Pubkey,
+    anime_title: &str,
+    edition: u64,
+) -> Instruction {
+    let print_token = get_associated_token_address(holder, print_mint);
+    let (print_metadata, _) = find_metadata_account(print_mint);
+    let (print_edition, _) = find_master_edition_account(print_mint);
+    let (print_nft_data, _) = NFTData::find_address(program_id, print_mint);
+    let (master_nft_data, _) = NFTData::find_address(program_id, master_mint);
+    let (master_metadata, _) = find_metadata_account(master_mint);
+    let (master_edition, _) = find_master_edition_account(master_mint);
+    let (edition_marker, _) = find_edition_marker_account(master_mint, edition);
+    let master_token = get_associated_token_address(holder, master_mint);
+    let (rarity_supply, _) = RaritySupplyData::find_address(program_id, anime_title);
+    let accounts = vec![
+        AccountMeta::new(*holder, true),
+        AccountMeta::new(*print_mint, true),
+        AccountMeta::new(print_token, false),
+        AccountMeta::new(print_metadata, false),
+        AccountMeta::new(print_edition, false),
+        AccountMeta::new(print_nft_data, false),
+        AccountMeta::new_readonly(*master_mint, false),
+        AccountMeta::new_readonly(master_nft_data, false),
+        AccountMeta::new_readonly(master_metadata, false),
+        AccountMeta::new(master_edition, false),
+        AccountMeta::new(edition_marker, false),
+        AccountMeta::new_readonly(master_token, false),
+        AccountMeta::new_readonly(*master_update_authority, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new(rarity_supply, false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::PrintEdition { edition };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a BurnNFT instruction
+///
+/// `collection_mint` is only needed for an NFT in a collection, `master_token`, a token
+/// account of the master mint, only to burn a print, and `authorities`, the metadata update
+/// authority and mint authority, only to burn an NFT without an edition.
+pub fn burn_nft(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    nft_mint: &Pubkey,
+    edition: Option<&NFTEdition>,
+    collection_mint: Option<&Pubkey>,
+    master_token: Option<&Pubkey>,
+    authorities: Option<(&Pubkey, Option<&Pubkey>)>,
+    redeem: bool,
+) -> Instruction {
+    let owner_token = get_associated_token_address(owner, nft_mint);
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let (nft_metadata, _) = find_metadata_account(nft_mint);
+    let (nft_edition, _) = find_master_edition_account(nft_mint);
+    let (redemption, _) = RedemptionData::find_address(program_id, nft_mint, owner);
+    let (freeze_authority, _) = NFTData::find_freeze_authority(program_id, nft_mint);
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(*nft_mint, false),
+        AccountMeta::new(owner_token, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(nft_metadata, false),
+        AccountMeta::new(nft_edition, false),
+        AccountMeta::new(redemption, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(mpl_token_metadata::id(), false),
+        AccountMeta::new_readonly(freeze_authority, false),
+    ];
+    if let Some(collection_mint) = collection_mint {
+        let (collection, _) = CollectionData::find_address(program_id, collection_mint);
+        let (collection_metadata, _) = find_metadata_account(collection_mint);
+        accounts.push(AccountMeta::new(collection, false));
+        accounts.push(AccountMeta::new(collection_metadata, false));
+    }
+    match edition {
+        Some(NFTEdition::Print { master_mint, number }) => {
+            let master_token = master_token
+                .copied()
+                .unwrap_or_else(|| get_associated_token_address(owner, master_mint));
+            let (master_edition, _) = find_master_edition_account(master_mint);
+            let (edition_marker, _) = find_edition_marker_account(master_mint, *number);
+            accounts.push(AccountMeta::new_readonly(*master_mint, false));
+            accounts.push(AccountMeta::new_readonly(master_token, false));
+            accounts.push(AccountMeta::new(master_edition, false));
+            accounts.push(AccountMeta::new(edition_marker, false));
+        }
+        Some(NFTEdition::Master { .. }) => {}
+        None => {
+            if let Some((update_authority, mint_authority)) = authorities {
+                accounts.push(AccountMeta::new_readonly(*update_authority, true));
+                if let Some(mint_authority) = mint_authority {
+                    accounts.push(AccountMeta::new_readonly(*mint_authority, true));
+                }
+            }
+        }
+    }
+
+    let instruction_data = OtakuVerseInstruction::BurnNFT { redeem };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a ReissueSoulboundNFT instruction
+pub fn reissue_soulbound_nft(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    holder: &Pubkey,
+    holder_token: &Pubkey,
+    new_wallet: &Pubkey,
+    new_wallet_signs: bool,
+    nft_mint: &Pubkey,
+) -> Instruction {
+    let (nft_data, _) = NFTData::find_address(program_id, nft_mint);
+    let new_wallet_token = get_associated_token_address(new_wallet, nft_mint);
+    let (freeze_authority, _) = NFTData::find_freeze_authority(program_id, nft_mint);
+    let (config, _) = ProgramConfig::find_address(program_id);
+    let accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(*holder, false),
+        AccountMeta::new_readonly(*new_wallet, new_wallet_signs),
+        AccountMeta::new(*nft_mint, false),
+        AccountMeta::new(nft_data, false),
+        AccountMeta::new(*holder_token, false),
+        AccountMeta::new(new_wallet_token, false),
+        AccountMeta::new_readonly(freeze_authority, false),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::ReissueSoulboundNFT;
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+/// Creates a MigrateAccount instruction
+pub fn migrate_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    account: &Pubkey,
+    account_type: LegacyAccountType,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(*account, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction_data = OtakuVerseInstruction::MigrateAccount { account_type };
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data.pack(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legacy(ordinal: u8, fields: impl BorshSerialize) -> Vec<u8> {
+        let mut data = vec![ordinal];
+        fields.serialize(&mut data).unwrap();
+        data
+    }
+
+    fn versioned(discriminator: u8, fields: impl BorshSerialize) -> Vec<u8> {
+        let mut data = vec![VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, discriminator];
+        fields.serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn pack_round_trips() {
+        let instructions = vec![
+            OtakuVerseInstruction::MintNFTReward {
+                name: "Reward".to_string(),
+                symbol: "OTKV".to_string(),
+                uri: "https://example.com/reward.json".to_string(),
+                anime_title: "Frieren".to_string(),
+                rarity: "legendary".to_string(),
+                soulbound: true,
+            },
+            OtakuVerseInstruction::ListNFTForSale {
+                price: 1_000,
+                listing_type: ListingType::FixedPrice,
+                payment_mint: Some(Pubkey::new_unique()),
+            },
+            OtakuVerseInstruction::CloseCommunity,
+            OtakuVerseInstruction::BanMember { user: Pubkey::new_unique() },
+            OtakuVerseInstruction::PlaceBid { amount: 42 },
+            OtakuVerseInstruction::BurnNFT { redeem: true },
+            OtakuVerseInstruction::ReissueSoulboundNFT,
+        ];
+
+        for instruction in instructions {
+            assert_eq!(OtakuVerseInstruction::unpack(&instruction.pack()).unwrap(), instruction);
+        }
+    }
+
+    #[test]
+    fn unpack_legacy_defaults_trailing_fields() {
+        let data = legacy(
+            discriminator::MINT_NFT_REWARD,
+            ("Reward", "OTKV", "https://example.com/reward.json", "Frieren", "rare"),
+        );
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data).unwrap(),
+            OtakuVerseInstruction::MintNFTReward {
+                name: "Reward".to_string(),
+                symbol: "OTKV".to_string(),
+                uri: "https://example.com/reward.json".to_string(),
+                anime_title: "Frieren".to_string(),
+                rarity: "rare".to_string(),
+                soulbound: false,
+            }
+        );
+
+        let data = legacy(discriminator::LIST_NFT_FOR_SALE, 500u64);
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data).unwrap(),
+            OtakuVerseInstruction::ListNFTForSale {
+                price: 500,
+                listing_type: ListingType::FixedPrice,
+                payment_mint: None,
+            }
+        );
+
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&[discriminator::CLOSE_COMMUNITY]).unwrap(),
+            OtakuVerseInstruction::CloseCommunity
+        );
+    }
+
+    #[test]
+    fn unpack_versioned_defaults_trailing_fields() {
+        let data = versioned(discriminator::LIST_NFT_FOR_SALE, 500u64);
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data).unwrap(),
+            OtakuVerseInstruction::ListNFTForSale {
+                price: 500,
+                listing_type: ListingType::FixedPrice,
+                payment_mint: None,
+            }
+        );
+
+        // Trailing fields are read in order, so a later one needs the earlier ones
+        let payment_mint = Pubkey::new_unique();
+        let data = versioned(
+            discriminator::LIST_NFT_FOR_SALE,
+            (500u64, ListingType::FixedPrice, Some(payment_mint)),
+        );
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data).unwrap(),
+            OtakuVerseInstruction::ListNFTForSale {
+                price: 500,
+                listing_type: ListingType::FixedPrice,
+                payment_mint: Some(payment_mint),
+            }
+        );
+    }
+
+    #[test]
+    fn unpack_rejects_malformed_data() {
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&[]),
+            Err(OtakuVerseError::InvalidInstructionData.into())
+        );
+        // Instructions added after the legacy layout was frozen need the versioned layout
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&legacy(LEGACY_INSTRUCTION_COUNT, 42u64)),
+            Err(OtakuVerseError::UnknownInstruction.into())
+        );
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&[VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION + 1, 0]),
+            Err(OtakuVerseError::UnsupportedInstructionVersion.into())
+        );
+        let data = [VERSIONED_INSTRUCTION_TAG, INSTRUCTION_VERSION, u8::MAX];
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data),
+            Err(OtakuVerseError::UnknownInstruction.into())
+        );
+
+        let mut data = versioned(discriminator::PLACE_BID, 42u64);
+        data.push(0);
+        assert_eq!(
+            OtakuVerseInstruction::unpack(&data),
+            Err(OtakuVerseError::InvalidInstructionData.into())
+        );
     }
 }
@@ -4,8 +4,15 @@ pub mod error;
 pub mod instruction;
 pub mod processor;
 pub mod state {
+    pub mod account;
     pub mod nft;
     pub mod community;
+    pub mod config;
+    pub mod supply;
+    pub mod collection;
+    pub mod offer;
+    pub mod auction;
+    pub mod redemption;
     pub mod r#mod;
 }
 
@@ -13,9 +20,12 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint,
     entrypoint::ProgramResult,
+    program_error::PrintProgramError,
     pubkey::Pubkey,
 };
 
+use crate::error::OtakuVerseError;
+
 entrypoint!(process_instruction);
 
 pub fn process_instruction(
@@ -23,5 +33,10 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    processor::process_instruction(program_id, accounts, instruction_data)
+    if let Err(error) = processor::process_instruction(program_id, accounts, instruction_data) {
+        // Log the human-readable message for program-specific errors
+        error.print::<OtakuVerseError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -1,16 +1,20 @@
-use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::ProgramResult,
+    hash::hash,
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
+    program_pack::Pack,
     pubkey::Pubkey,
     system_instruction,
+    system_program,
     sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 use spl_token::instruction as token_instruction;
 use mpl_token_metadata::instruction as metadata_instruction;
+use mpl_token_metadata::state::{CollectionDetails, DataV2, Metadata, TokenMetadataAccount};
 // Ensure these are imported for clarity
 use spl_token;
 use mpl_token_metadata;
@@ -19,8 +23,23 @@ use crate::{
     error::OtakuVerseError,
     instruction::OtakuVerseInstruction,
     state::{
-        nft::{NFTData, NFTAttribute, NFTRarity},
-        community::{CommunityData, MessageData},
+        account::{LegacyAccountType, VersionedAccount},
+        nft::{
+            ListingType, NFTData, NFTAttribute, NFTAttributeV1, NFTCreator, NFTEdition, NFTRarity,
+            ESCROW_SEED, MAX_ANIME_TITLE_LEN, MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN,
+            NFT_DATA_SEED, SOULBOUND_SEED,
+        },
+        community::{
+            BanData, ClosedCommunityData, CommunityData, MembershipData, MessageData,
+            ReactionData, BAN_SEED, COMMUNITY_SEED, MAX_COMMUNITY_ID_LEN, MAX_MESSAGE_LEN,
+            MEMBERSHIP_SEED, MESSAGE_SEED, REACTION_SEED,
+        },
+        config::{ProgramConfig, CONFIG_SEED},
+        supply::{RaritySupplyData, RARITY_SUPPLY_SEED},
+        collection::{CollectionData, COLLECTION_SEED},
+        offer::{OfferData, OFFER_SEED},
+        auction::{AuctionData, AUCTION_SEED},
+        redemption::{RedemptionData, REDEMPTION_SEED},
     },
 };
 
@@ -30,8 +49,7 @@ pub fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = OtakuVerseInstruction::try_from_slice(instruction_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let instruction = OtakuVerseInstruction::unpack(instruction_data)?;
 
     match instruction {
         OtakuVerseInstruction::MintNFTReward {
@@ -40,7 +58,17 @@ pub fn process_instruction(
             uri,
             anime_title,
             rarity,
-        } => process_mint_nft_reward(program_id, accounts, name, symbol, uri, anime_title, rarity),
+            soulbound,
+        } => process_mint_nft_reward(
+            program_id,
+            accounts,
+            name,
+            symbol,
+            uri,
+            anime_title,
+            rarity,
+            soulbound,
+        ),
         OtakuVerseInstruction::MintEnhancedNFT {
             name,
             symbol,
@@ -51,6 +79,9 @@ pub fn process_instruction(
             collection_id,
             attributes,
             royalty_basis_points,
+            creators,
+            typed_attributes,
+            max_supply,
         } => process_mint_enhanced_nft(
             program_id,
             accounts,
@@ -63,6 +94,9 @@ pub fn process_instruction(
             collection_id,
             attributes,
             royalty_basis_points,
+            creators,
+            typed_attributes,
+            max_supply,
         ),
         OtakuVerseInstruction::PurchaseNFT { price } => {
             process_purchase_nft(program_id, accounts, price)
@@ -73,6 +107,7 @@ pub fn process_instruction(
             description,
             attributes_to_add,
             royalty_basis_points,
+            typed_attributes_to_add,
         } => process_update_nft_metadata(
             program_id,
             accounts,
@@ -81,12 +116,13 @@ pub fn process_instruction(
             description,
             attributes_to_add,
             royalty_basis_points,
+            typed_attributes_to_add,
         ),
         OtakuVerseInstruction::VerifyNFT { mint } => {
             process_verify_nft(program_id, accounts, mint)
         }
-        OtakuVerseInstruction::ListNFTForSale { price } => {
-            process_list_nft_for_sale(program_id, accounts, price)
+        OtakuVerseInstruction::ListNFTForSale { price, listing_type, payment_mint } => {
+            process_list_nft_for_sale(program_id, accounts, price, listing_type, payment_mint)
         }
         OtakuVerseInstruction::CancelNFTListing => {
             process_cancel_nft_listing(program_id, accounts)
@@ -94,7 +130,132 @@ pub fn process_instruction(
         OtakuVerseInstruction::SendCommunityMessage {
             community_id,
             content,
-        } => process_send_community_message(program_id, accounts, community_id, content),
+            reply_to,
+        } => process_send_community_message(program_id, accounts, community_id, content, reply_to),
+        OtakuVerseInstruction::InitializeConfig {
+            treasury,
+            platform_fee_basis_points,
+        } => process_initialize_config(program_id, accounts, treasury, platform_fee_basis_points),
+        OtakuVerseInstruction::UpdateConfig {
+            treasury,
+            platform_fee_basis_points,
+        } => process_update_config(program_id, accounts, treasury, platform_fee_basis_points),
+        OtakuVerseInstruction::UnverifyNFT { mint } => {
+            process_unverify_nft(program_id, accounts, mint)
+        }
+        OtakuVerseInstruction::AddAdmin { admin } => {
+            process_add_admin(program_id, accounts, admin)
+        }
+        OtakuVerseInstruction::RemoveAdmin { admin } => {
+            process_remove_admin(program_id, accounts, admin)
+        }
+        OtakuVerseInstruction::CreateCommunity {
+            id,
+            name,
+            description,
+        } => process_create_community(program_id, accounts, id, name, description),
+        OtakuVerseInstruction::UpdateCommunity { name, description } => {
+            process_update_community(program_id, accounts, name, description)
+        }
+        OtakuVerseInstruction::CloseCommunity => {
+            process_close_community(program_id, accounts)
+        }
+        OtakuVerseInstruction::JoinCommunity => {
+            process_join_community(program_id, accounts)
+        }
+        OtakuVerseInstruction::LeaveCommunity => {
+            process_leave_community(program_id, accounts)
+        }
+        OtakuVerseInstruction::AddModerator { moderator } => {
+            process_add_moderator(program_id, accounts, moderator)
+        }
+        OtakuVerseInstruction::RemoveModerator { moderator } => {
+            process_remove_moderator(program_id, accounts, moderator)
+        }
+        OtakuVerseInstruction::TransferCommunityOwnership { new_creator } => {
+            process_transfer_community_ownership(program_id, accounts, new_creator)
+        }
+        OtakuVerseInstruction::SetMessageHidden { hidden } => {
+            process_set_message_hidden(program_id, accounts, hidden)
+        }
+        OtakuVerseInstruction::DeleteMessage => {
+            process_delete_message(program_id, accounts)
+        }
+        OtakuVerseInstruction::BanMember { user } => {
+            process_ban_member(program_id, accounts, user)
+        }
+        OtakuVerseInstruction::UnbanMember { user } => {
+            process_unban_member(program_id, accounts, user)
+        }
+        OtakuVerseInstruction::AddReaction { emoji_code } => {
+            process_add_reaction(program_id, accounts, emoji_code)
+        }
+        OtakuVerseInstruction::RemoveReaction { emoji_code } => {
+            process_remove_reaction(program_id, accounts, emoji_code)
+        }
+        OtakuVerseInstruction::MigrateNFTData => {
+            process_migrate_nft_data(program_id, accounts)
+        }
+        OtakuVerseInstruction::SetRaritySupplyCaps { anime_title, caps } => {
+            process_set_rarity_supply_caps(program_id, accounts, anime_title, caps)
+        }
+        OtakuVerseInstruction::CreateCollection { name, symbol, uri } => {
+            process_create_collection(program_id, accounts, name, symbol, uri)
+        }
+        OtakuVerseInstruction::EditNFTAttributes { remove, set, uri } => {
+            process_edit_nft_attributes(program_id, accounts, remove, set, uri)
+        }
+        OtakuVerseInstruction::MakeOffer { price, expires_at } => {
+            process_make_offer(program_id, accounts, price, expires_at)
+        }
+        OtakuVerseInstruction::AcceptOffer { price } => {
+            process_accept_offer(program_id, accounts, price)
+        }
+        OtakuVerseInstruction::CancelOffer => {
+            process_cancel_offer(program_id, accounts)
+        }
+        OtakuVerseInstruction::CreateAuction {
+            reserve_price,
+            min_bid_increment,
+            start_at,
+            end_at,
+            extension_seconds,
+        } => process_create_auction(
+            program_id,
+            accounts,
+            reserve_price,
+            min_bid_increment,
+            start_at,
+            end_at,
+            extension_seconds,
+        ),
+        OtakuVerseInstruction::PlaceBid { amount } => {
+            process_place_bid(program_id, accounts, amount)
+        }
+        OtakuVerseInstruction::SettleAuction => {
+            process_settle_auction(program_id, accounts)
+        }
+        OtakuVerseInstruction::AddPaymentMint { mint } => {
+            process_add_payment_mint(program_id, accounts, mint)
+        }
+        OtakuVerseInstruction::RemovePaymentMint { mint } => {
+            process_remove_payment_mint(program_id, accounts, mint)
+        }
+        OtakuVerseInstruction::CreateMasterEdition { max_supply } => {
+            process_create_master_edition(program_id, accounts, max_supply)
+        }
+        OtakuVerseInstruction::PrintEdition { edition } => {
+            process_print_edition(program_id, accounts, edition)
+        }
+        OtakuVerseInstruction::BurnNFT { redeem } => {
+            process_burn_nft(program_id, accounts, redeem)
+        }
+        OtakuVerseInstruction::ReissueSoulboundNFT => {
+            process_reissue_soulbound_nft(program_id, accounts)
+        }
+        OtakuVerseInstruction::MigrateAccount { account_type } => {
+            process_migrate_account(program_id, accounts, account_type)
+        }
     }
 }
 
@@ -107,6 +268,7 @@ fn process_mint_nft_reward(
     uri: String,
     anime_title: String,
     rarity: String,
+    soulbound: bool,
 ) -> ProgramResult {
     msg!("Processing MintNFTReward instruction");
 
@@ -122,12 +284,73 @@ fn process_mint_nft_reward(
     let token_program_info = next_account_info(account_info_iter)?;
     let token_metadata_program_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let rarity_supply_info = next_account_info(account_info_iter)?;
 
     // Verify the authority is a signer
     if !authority_info.is_signer {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
+    // Soulbound rewards are frozen by the program, so it keeps the freeze authority. It
+    // also keeps the mint authority so it can reissue them to a holder's new wallet, while
+    // other rewards hand both to a master edition that allows no prints.
+    let (freeze_authority, freeze_bump) =
+        NFTData::find_freeze_authority(program_id, nft_mint_info.key);
+    let (freeze_authority_info, master_edition_info) = if soulbound {
+        let freeze_authority_info = next_account_info(account_info_iter)?;
+        if freeze_authority != *freeze_authority_info.key
+            || freeze_authority != *mint_authority_info.key
+        {
+            return Err(OtakuVerseError::InvalidPDA.into());
+        }
+        (Some(freeze_authority_info), None)
+    } else {
+        (None, Some(next_account_info(account_info_iter)?))
+    };
+    let freeze_seeds: &[&[u8]] = &[SOULBOUND_SEED, nft_mint_info.key.as_ref(), &[freeze_bump]];
+    let soulbound_signer = [freeze_seeds];
+    let mint_authority_seeds: &[&[&[u8]]] = if soulbound { &soulbound_signer } else { &[] };
+
+    // Verify the NFT data account is the mint's PDA and not yet created
+    let (nft_data_pda, nft_data_bump) = NFTData::find_address(program_id, nft_mint_info.key);
+    if nft_data_pda != *nft_data_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !nft_data_info.data_is_empty() {
+        return Err(OtakuVerseError::NFTAlreadyExists.into());
+    }
+
+    // Get the current timestamp
+    let clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp;
+
+    // Create NFT data
+    let mut nft_data = Box::new(NFTData::new_with_details(
+        *nft_mint_info.key,
+        *authority_info.key,
+        name,
+        symbol,
+        uri,
+        anime_title,
+        rarity.parse::<NFTRarity>()?,
+        *authority_info.key,
+        String::new(),
+        None,
+        vec![],
+        0,
+        vec![],
+        current_timestamp,
+    ));
+    nft_data.is_soulbound = soulbound;
+    nft_data.validate()?;
+
+    // Count the mint against the anime's supply cap for its rarity
+    let mut rarity_supply =
+        load_rarity_supply(program_id, rarity_supply_info, &nft_data.anime_title)?;
+    rarity_supply.record_mint(&nft_data.rarity)?;
+    rarity_supply.pack_into_slice(&mut rarity_supply_info.data.borrow_mut())?;
+
     // Create mint account
     let rent = Rent::from_account_info(rent_info)?;
     let mint_rent = rent.minimum_balance(spl_token::state::Mint::LEN);
@@ -149,7 +372,7 @@ fn process_mint_nft_reward(
             spl_token::id(),
             nft_mint_info.key,
             mint_authority_info.key,
-            Some(update_authority_info.key),
+            Some(if soulbound { &freeze_authority } else { mint_authority_info.key }),
             0,
         )?,
         &[
@@ -177,7 +400,7 @@ fn process_mint_nft_reward(
     )?;
 
     // Mint token
-    invoke(
+    invoke_signed(
         &token_instruction::mint_to(
             spl_token::id(),
             nft_mint_info.key,
@@ -192,10 +415,64 @@ fn process_mint_nft_reward(
             mint_authority_info.clone(),
             token_program_info.clone(),
         ],
+        mint_authority_seeds,
     )?;
 
+    // Freeze the reward in the recipient's token account, after letting the freeze
+    // authority burn and close it so an admin can reissue it without the holder
+    if let Some(freeze_authority_info) = freeze_authority_info {
+        invoke(
+            &token_instruction::approve(
+                spl_token::id(),
+                nft_token_info.key,
+                freeze_authority_info.key,
+                authority_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                nft_token_info.clone(),
+                freeze_authority_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke(
+            &token_instruction::set_authority(
+                spl_token::id(),
+                nft_token_info.key,
+                Some(freeze_authority_info.key),
+                token_instruction::AuthorityType::CloseAccount,
+                authority_info.key,
+                &[],
+            )?,
+            &[
+                nft_token_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        invoke_signed(
+            &token_instruction::freeze_account(
+                spl_token::id(),
+                nft_token_info.key,
+                nft_mint_info.key,
+                freeze_authority_info.key,
+                &[],
+            )?,
+            &[
+                nft_token_info.clone(),
+                nft_mint_info.clone(),
+                freeze_authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[freeze_seeds],
+        )?;
+    }
+
     // Create metadata
-    invoke(
+    invoke_signed(
         &metadata_instruction::create_metadata_accounts_v3(
             *token_metadata_program_info.key,
             *nft_metadata_info.key,
@@ -203,9 +480,9 @@ fn process_mint_nft_reward(
             *mint_authority_info.key,
             *authority_info.key,
             *update_authority_info.key,
-            name,
-            symbol,
-            uri,
+            nft_data.name.clone(),
+            nft_data.symbol.clone(),
+            nft_data.uri.clone(),
             None,
             0,
             true,
@@ -224,28 +501,49 @@ fn process_mint_nft_reward(
             rent_info.clone(),
             token_metadata_program_info.clone(),
         ],
+        mint_authority_seeds,
     )?;
 
-    // Get the current timestamp
-    let clock = Clock::get()?;
-    let current_timestamp = clock.unix_timestamp;
+    // Give transferable rewards their master edition, so burning them closes the metadata
+    if let Some(master_edition_info) = master_edition_info {
+        invoke(
+            &metadata_instruction::create_master_edition_v3(
+                *token_metadata_program_info.key,
+                *master_edition_info.key,
+                *nft_mint_info.key,
+                *update_authority_info.key,
+                *mint_authority_info.key,
+                *nft_metadata_info.key,
+                *authority_info.key,
+                Some(0),
+            ),
+            &[
+                master_edition_info.clone(),
+                nft_mint_info.clone(),
+                update_authority_info.clone(),
+                mint_authority_info.clone(),
+                authority_info.clone(),
+                nft_metadata_info.clone(),
+                token_program_info.clone(),
+                system_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+        )?;
+        nft_data.edition = Some(NFTEdition::Master { max_supply: Some(0) });
+    }
 
-    // Create NFT data
-    let nft_data = Box::new(NFTData::new(
-        *nft_mint_info.key,
-        *authority_info.key,
-        name,
-        symbol,
-        uri,
-        anime_title,
-        rarity,
-        0,
-        current_timestamp,
-    ));
+    // Store NFT data in the mint's PDA
+    store_new_nft_data(
+        program_id,
+        authority_info,
+        nft_data_info,
+        system_program_info,
+        &rent,
+        &nft_data,
+        nft_data_bump,
+    )?;
 
-    // Store NFT data (in a real implementation, this would be stored on-chain)
-    // For simplicity, we're just logging it here
-    msg!("NFT minted successfully: {:?}", nft_data);
+    msg!("NFT minted successfully: {}", nft_data.mint);
 
     Ok(())
 }
@@ -264,44 +562,163 @@ fn process_purchase_nft(
     let seller_info = next_account_info(account_info_iter)?;
     let nft_mint_info = next_account_info(account_info_iter)?;
     let buyer_token_info = next_account_info(account_info_iter)?;
-    let seller_token_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let payment_infos = account_info_iter.as_slice();
 
     // Verify the buyer is a signer
     if !buyer_info.is_signer {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
-    // Transfer SOL from buyer to seller
-    invoke(
-        &system_instruction::transfer(buyer_info.key, seller_info.key, price),
-        &[
-            buyer_info.clone(),
-            seller_info.clone(),
-            system_program_info.clone(),
-        ],
+    // Verify the listing
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if !nft_data.is_for_sale {
+        return Err(OtakuVerseError::NotListed.into());
+    }
+    // A declining price is charged at its current value, never more than the buyer offered
+    let sale_price = nft_data.current_price(Clock::get()?.unix_timestamp);
+    let price_matches = match nft_data.listing_type {
+        ListingType::FixedPrice => sale_price == price,
+        ListingType::DecliningPrice { .. } => sale_price <= price,
+    };
+    if !price_matches {
+        return Err(OtakuVerseError::PriceMismatch.into());
+    }
+    if nft_data.owner != *seller_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+
+    let config = load_config(program_id, config_info)?;
+    if config.treasury != *treasury_info.key {
+        return Err(OtakuVerseError::TreasuryMismatch.into());
+    }
+
+    let escrow_bump = check_escrow_accounts(
+        program_id,
+        nft_mint_info.key,
+        escrow_authority_info,
+        escrow_token_info,
     )?;
 
-    // Transfer NFT from seller to buyer
-    invoke(
-        &token_instruction::transfer(
-            spl_token::id(),
-            seller_token_info.key,
-            buyer_token_info.key,
-            seller_info.key,
-            &[],
-            1,
-        )?,
-        &[
-            seller_token_info.clone(),
-            buyer_token_info.clone(),
-            seller_info.clone(),
-            token_program_info.clone(),
-        ],
+    let buyer_token = unpack_token_account(buyer_token_info)?;
+    if buyer_token.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if buyer_token.owner != *buyer_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+
+    // Split the payment between creators, platform and seller, paying lamports
+    // between wallets or tokens between payment token accounts
+    let payment = match nft_data.payment_mint {
+        None => {
+            let payment = split_sale_payment(
+                &nft_data,
+                &config,
+                sale_price,
+                payment_infos,
+                treasury_info,
+                seller_info,
+                None,
+            )?;
+            for (receiver_info, amount) in payment.payouts() {
+                if amount == 0 {
+                    continue;
+                }
+                invoke(
+                    &system_instruction::transfer(buyer_info.key, receiver_info.key, amount),
+                    &[
+                        buyer_info.clone(),
+                        receiver_info.clone(),
+                        system_program_info.clone(),
+                    ],
+                )?;
+            }
+            payment
+        }
+        Some(payment_mint) => {
+            let [
+                buyer_payment_info,
+                seller_payment_info,
+                treasury_payment_info,
+                creator_payment_infos @ ..,
+            ] = payment_infos
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            check_payment_token_account(buyer_payment_info, &payment_mint, buyer_info.key)?;
+            check_payment_token_account(seller_payment_info, &payment_mint, seller_info.key)?;
+            check_payment_token_account(treasury_payment_info, &payment_mint, &config.treasury)?;
+
+            let payment = split_sale_payment(
+                &nft_data,
+                &config,
+                sale_price,
+                creator_payment_infos,
+                treasury_payment_info,
+                seller_payment_info,
+                Some(&payment_mint),
+            )?;
+            for (receiver_info, amount) in payment.payouts() {
+                if amount == 0 {
+                    continue;
+                }
+                invoke(
+                    &token_instruction::transfer(
+                        spl_token::id(),
+                        buyer_payment_info.key,
+                        receiver_info.key,
+                        buyer_info.key,
+                        &[],
+                        amount,
+                    )?,
+                    &[
+                        buyer_payment_info.clone(),
+                        receiver_info.clone(),
+                        buyer_info.clone(),
+                        token_program_info.clone(),
+                    ],
+                )?;
+            }
+            payment
+        }
+    };
+
+    // Transfer NFT from escrow to buyer
+    release_from_escrow(
+        nft_mint_info.key,
+        escrow_token_info,
+        escrow_authority_info,
+        buyer_token_info,
+        seller_info,
+        token_program_info,
+        escrow_bump,
     )?;
 
-    msg!("NFT purchased successfully");
+    nft_data.transfer(*buyer_info.key);
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    let currency = match nft_data.payment_mint {
+        Some(payment_mint) => format!("units of {}", payment_mint),
+        None => "lamports".to_string(),
+    };
+    msg!("NFT purchased successfully for {} {}", sale_price, currency);
+    if price > sale_price {
+        msg!("Overpayment of {} {} not charged", price - sale_price, currency);
+    }
+    msg!(
+        "Royalty: {} {}, platform fee: {} {}",
+        payment.royalty_total(),
+        currency,
+        payment.platform_fee,
+        currency
+    );
 
     Ok(())
 }
@@ -317,8 +734,11 @@ fn process_mint_enhanced_nft(
     rarity: NFTRarity,
     description: String,
     collection_id: Option<Pubkey>,
-    attributes: Vec<NFTAttribute>,
+    attributes: Vec<NFTAttributeV1>,
     royalty_basis_points: u16,
+    creators: Vec<NFTCreator>,
+    typed_attributes: Vec<NFTAttribute>,
+    max_supply: Option<u64>,
 ) -> ProgramResult {
     msg!("Processing MintEnhancedNFT instruction");
 
@@ -334,12 +754,66 @@ fn process_mint_enhanced_nft(
     let token_program_info = next_account_info(account_info_iter)?;
     let token_metadata_program_info = next_account_info(account_info_iter)?;
     let rent_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let rarity_supply_info = next_account_info(account_info_iter)?;
+    let master_edition_info = next_account_info(account_info_iter)?;
 
     // Verify the authority is a signer
     if !authority_info.is_signer {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
+    // Verify the NFT data account is the mint's PDA and not yet created
+    let (nft_data_pda, nft_data_bump) = NFTData::find_address(program_id, nft_mint_info.key);
+    if nft_data_pda != *nft_data_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !nft_data_info.data_is_empty() {
+        return Err(OtakuVerseError::NFTAlreadyExists.into());
+    }
+
+    // Older clients send no creators, so the authority takes the full share
+    let creators = if creators.is_empty() {
+        vec![NFTCreator {
+            address: *authority_info.key,
+            share: 100,
+        }]
+    } else {
+        creators
+    };
+
+    // Get the current timestamp
+    let clock = Clock::get()?;
+    let current_timestamp = clock.unix_timestamp;
+
+    // Create NFT data with enhanced metadata (not verified by default)
+    let mut nft_data = Box::new(NFTData::new_with_details(
+        *nft_mint_info.key,
+        *authority_info.key,
+        name,
+        symbol,
+        uri,
+        anime_title,
+        rarity,
+        *authority_info.key,
+        description,
+        None,
+        attributes.into_iter().map(NFTAttribute::from).collect(),
+        royalty_basis_points,
+        creators,
+        current_timestamp,
+    ));
+    for attribute in typed_attributes {
+        nft_data.set_attribute(attribute);
+    }
+    nft_data.validate()?;
+
+    // Count the mint against the anime's supply cap for its rarity
+    let mut rarity_supply =
+        load_rarity_supply(program_id, rarity_supply_info, &nft_data.anime_title)?;
+    rarity_supply.record_mint(&nft_data.rarity)?;
+    rarity_supply.pack_into_slice(&mut rarity_supply_info.data.borrow_mut())?;
+
     // Create mint account
     let rent = Rent::from_account_info(rent_info)?;
     let mint_rent = rent.minimum_balance(spl_token::state::Mint::LEN);
@@ -361,7 +835,7 @@ fn process_mint_enhanced_nft(
             spl_token::id(),
             nft_mint_info.key,
             mint_authority_info.key,
-            Some(update_authority_info.key),
+            Some(mint_authority_info.key),
             0,
         )?,
         &[
@@ -406,18 +880,16 @@ fn process_mint_enhanced_nft(
         ],
     )?;
 
-    // Create metadata
-    // Convert royalty basis points to creators share
-    let creators_share = royalty_basis_points / 100;
-
-    // Create metadata with royalties
-    let creators = vec![
-        mpl_token_metadata::state::Creator {
-            address: *authority_info.key,
-            verified: true,
-            share: creators_share,
-        },
-    ];
+    // Create metadata with royalties, verifying the authority as a creator since it signs
+    let creators = nft_data
+        .creators
+        .iter()
+        .map(|creator| mpl_token_metadata::state::Creator {
+            address: creator.address,
+            verified: creator.address == *authority_info.key,
+            share: creator.share,
+        })
+        .collect();
 
     invoke(
         &metadata_instruction::create_metadata_accounts_v3(
@@ -427,9 +899,9 @@ fn process_mint_enhanced_nft(
             *mint_authority_info.key,
             *authority_info.key,
             *update_authority_info.key,
-            name.clone(),
-            symbol.clone(),
-            uri.clone(),
+            nft_data.name.clone(),
+            nft_data.symbol.clone(),
+            nft_data.uri.clone(),
             Some(creators),
             royalty_basis_points,
             true,
@@ -450,32 +922,91 @@ fn process_mint_enhanced_nft(
         ],
     )?;
 
-    // Get the current timestamp
-    let clock = Clock::get()?;
-    let current_timestamp = clock.unix_timestamp;
+    // Hand the mint and freeze authorities to a master edition, so token metadata treats
+    // the NFT as non-fungible and closes its metadata when it is burned
+    invoke(
+        &metadata_instruction::create_master_edition_v3(
+            *token_metadata_program_info.key,
+            *master_edition_info.key,
+            *nft_mint_info.key,
+            *update_authority_info.key,
+            *mint_authority_info.key,
+            *nft_metadata_info.key,
+            *authority_info.key,
+            max_supply,
+        ),
+        &[
+            master_edition_info.clone(),
+            nft_mint_info.clone(),
+            update_authority_info.clone(),
+            mint_authority_info.clone(),
+            authority_info.clone(),
+            nft_metadata_info.clone(),
+            token_program_info.clone(),
+            system_program_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+    )?;
+    nft_data.edition = Some(NFTEdition::Master { max_supply });
 
-    // Create NFT data with enhanced metadata
-    let nft_data = Box::new(NFTData::new_with_details(
-        *nft_mint_info.key,
-        *authority_info.key,
-        name,
-        symbol,
-        uri,
-        anime_title,
-        rarity,
-        0,
-        current_timestamp,
-        *authority_info.key,
-        description,
-        collection_id,
-        attributes,
-        royalty_basis_points,
-        false, // Not verified by default
-    ));
+    // Verify the NFT into its collection so wallets and marketplaces group it
+    if let Some(collection_mint) = collection_id {
+        let collection_info = next_account_info(account_info_iter)?;
+        let collection_mint_info = next_account_info(account_info_iter)?;
+        let collection_metadata_info = next_account_info(account_info_iter)?;
+        let collection_master_edition_info = next_account_info(account_info_iter)?;
+
+        if collection_mint != *collection_mint_info.key {
+            return Err(OtakuVerseError::MintMismatch.into());
+        }
+        let (mut collection, collection_bump) =
+            load_collection(program_id, collection_info, &collection_mint)?;
+        if collection.authority != *authority_info.key {
+            return Err(OtakuVerseError::NotCollectionAuthority.into());
+        }
+
+        invoke_signed(
+            &metadata_instruction::set_and_verify_sized_collection_item(
+                mpl_token_metadata::id(),
+                *nft_metadata_info.key,
+                *collection_info.key,
+                *authority_info.key,
+                *update_authority_info.key,
+                collection_mint,
+                *collection_metadata_info.key,
+                *collection_master_edition_info.key,
+                None,
+            ),
+            &[
+                nft_metadata_info.clone(),
+                collection_info.clone(),
+                authority_info.clone(),
+                update_authority_info.clone(),
+                collection_mint_info.clone(),
+                collection_metadata_info.clone(),
+                collection_master_edition_info.clone(),
+                token_metadata_program_info.clone(),
+            ],
+            &[&[COLLECTION_SEED, collection_mint.as_ref(), &[collection_bump]]],
+        )?;
+
+        collection.add_item()?;
+        collection.pack_into_slice(&mut collection_info.data.borrow_mut())?;
+        nft_data.set_collection(collection_mint);
+    }
 
-    // Store NFT data (in a real implementation, this would be stored on-chain)
-    // For simplicity, we're just logging it here
-    msg!("Enhanced NFT minted successfully: {:?}", nft_data);
+    // Store NFT data in the mint's PDA
+    store_new_nft_data(
+        program_id,
+        authority_info,
+        nft_data_info,
+        system_program_info,
+        &rent,
+        &nft_data,
+        nft_data_bump,
+    )?;
+
+    msg!("Enhanced NFT minted successfully: {}", nft_data.mint);
 
     Ok(())
 }
@@ -487,35 +1018,86 @@ fn process_update_nft_metadata(
     uri: Option<String>,
     name: Option<String>,
     description: Option<String>,
-    attributes_to_add: Vec<NFTAttribute>,
+    attributes_to_add: Vec<NFTAttributeV1>,
     royalty_basis_points: Option<u16>,
+    typed_attributes_to_add: Vec<NFTAttribute>,
 ) -> ProgramResult {
     msg!("Processing UpdateNFTMetadata instruction");
 
     // Get accounts
     let account_info_iter = &mut accounts.iter();
-    let owner_info = next_account_info(account_info_iter)?;
-    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
     let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
 
-    // Verify the owner is a signer
-    if !owner_info.is_signer {
+    // Verify the update authority is a signer
+    if !update_authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if *token_metadata_program_info.key != mpl_token_metadata::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Only the token metadata update authority may change the NFT
+    let metadata = Metadata::from_account_info(nft_metadata_info)?;
+    if metadata.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if metadata.update_authority != *update_authority_info.key {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
-    // In a real implementation, we would:
-    // 1. Fetch the NFT data from the account
-    // 2. Verify the owner is the actual owner of the NFT
-    // 3. Update the metadata
-    // 4. Store the updated metadata
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    if let Some(uri) = uri {
+        nft_data.uri = uri;
+    }
+    if let Some(name) = name {
+        nft_data.name = name;
+    }
+    if let Some(description) = description {
+        nft_data.description = description;
+    }
+    for attribute in attributes_to_add {
+        nft_data.set_attribute(attribute.into());
+    }
+    for attribute in typed_attributes_to_add {
+        nft_data.set_attribute(attribute);
+    }
+    if let Some(royalty_basis_points) = royalty_basis_points {
+        if royalty_basis_points > 10000 {
+            return Err(OtakuVerseError::InvalidBasisPoints.into());
+        }
+        nft_data.set_royalty(royalty_basis_points);
+    }
+
+    nft_data.validate()?;
+
+    // Grow records created before the bounded layout to the full size
+    if nft_data_info.data_len() < NFTData::LEN {
+        resize_account(
+            nft_data_info,
+            update_authority_info,
+            system_program_info,
+            NFTData::LEN,
+        )?;
+    }
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    // Keep the token metadata in sync so wallets see the changes
+    update_token_metadata(
+        &nft_data,
+        metadata,
+        nft_metadata_info,
+        update_authority_info,
+        token_metadata_program_info,
+    )?;
 
-    // For simplicity, we'll just log the update
-    msg!("NFT metadata updated successfully");
-    msg!("URI: {:?}", uri);
-    msg!("Name: {:?}", name);
-    msg!("Description: {:?}", description);
-    msg!("Attributes to add: {:?}", attributes_to_add);
-    msg!("Royalty basis points: {:?}", royalty_basis_points);
+    msg!("NFT metadata updated successfully: {}", nft_data.mint);
 
     Ok(())
 }
@@ -528,58 +1110,180 @@ fn process_verify_nft(
 ) -> ProgramResult {
     msg!("Processing VerifyNFT instruction");
 
-    // Get accounts
-    let account_info_iter = &mut accounts.iter();
-    let authority_info = next_account_info(account_info_iter)?;
-    let nft_metadata_info = next_account_info(account_info_iter)?;
-    let nft_mint_info = next_account_info(account_info_iter)?;
-
-    // Verify the authority is a signer
-    if !authority_info.is_signer {
-        return Err(OtakuVerseError::Unauthorized.into());
-    }
-
-    // In a real implementation, we would:
-    // 1. Verify the authority is an OtakuVerse admin
-    // 2. Fetch the NFT data from the account
-    // 3. Verify the mint address matches
-    // 4. Set the NFT as verified
-    // 5. Store the updated NFT data
+    set_nft_verified(program_id, accounts, mint, true)?;
 
-    // For simplicity, we'll just log the verification
     msg!("NFT verified successfully: {}", mint);
 
     Ok(())
 }
 
-/// Process ListNFTForSale instruction
-fn process_list_nft_for_sale(
+/// Process UnverifyNFT instruction
+fn process_unverify_nft(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    price: u64,
+    mint: Pubkey,
 ) -> ProgramResult {
-    msg!("Processing ListNFTForSale instruction");
+    msg!("Processing UnverifyNFT instruction");
 
-    // Get accounts
-    let account_info_iter = &mut accounts.iter();
-    let owner_info = next_account_info(account_info_iter)?;
-    let nft_metadata_info = next_account_info(account_info_iter)?;
-    let nft_mint_info = next_account_info(account_info_iter)?;
+    set_nft_verified(program_id, accounts, mint, false)?;
 
-    // Verify the owner is a signer
-    if !owner_info.is_signer {
+    msg!("NFT verification revoked: {}", mint);
+
+    Ok(())
+}
+
+/// Shared admin-gated body of VerifyNFT and UnverifyNFT
+fn set_nft_verified(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    verified: bool,
+) -> ProgramResult {
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    // Verify the authority is a signing OtakuVerse admin
+    let config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if !config.is_admin(authority_info.key) {
+        return Err(OtakuVerseError::NotAdmin.into());
+    }
+
+    if mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, &mint)?;
+    if verified {
+        nft_data.verify();
+    } else {
+        nft_data.unverify();
+    }
+    save_nft_data(nft_data_info, &nft_data)
+}
+
+/// Process ListNFTForSale instruction
+fn process_list_nft_for_sale(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+    listing_type: ListingType,
+    payment_mint: Option<Pubkey>,
+) -> ProgramResult {
+    msg!("Processing ListNFTForSale instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the owner is a signer
+    if !owner_info.is_signer {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
-    // In a real implementation, we would:
-    // 1. Fetch the NFT data from the account
-    // 2. Verify the owner is the actual owner of the NFT
-    // 3. Set the NFT for sale with the specified price
-    // 4. Store the updated NFT data
+    if price == 0 {
+        return Err(OtakuVerseError::InvalidPrice.into());
+    }
+    listing_type.validate(price)?;
+
+    // Verify the payment mint is accepted
+    if let Some(payment_mint) = &payment_mint {
+        let config_info = next_account_info(account_info_iter)?;
+        let config = load_config(program_id, config_info)?;
+        if !config.accepts_payment_mint(payment_mint) {
+            return Err(OtakuVerseError::PaymentMintNotAccepted.into());
+        }
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if nft_data.is_for_sale {
+        return Err(OtakuVerseError::AlreadyListed.into());
+    }
+    if nft_data.is_soulbound {
+        return Err(OtakuVerseError::SoulboundNFT.into());
+    }
+
+    // Verify the signer holds the NFT
+    check_token_holder(owner_token_info, nft_mint_info.key, owner_info.key)?;
+
+    check_escrow_accounts(
+        program_id,
+        nft_mint_info.key,
+        escrow_authority_info,
+        escrow_token_info,
+    )?;
+
+    // Create the escrow token account if this is the first listing
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            owner_info.key,
+            escrow_authority_info.key,
+            nft_mint_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            owner_info.clone(),
+            escrow_token_info.clone(),
+            escrow_authority_info.clone(),
+            nft_mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+
+    // Move the NFT into escrow
+    invoke(
+        &token_instruction::transfer(
+            spl_token::id(),
+            owner_token_info.key,
+            escrow_token_info.key,
+            owner_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            owner_token_info.clone(),
+            escrow_token_info.clone(),
+            owner_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // The token account is the source of truth for ownership
+    nft_data.owner = *owner_info.key;
+    nft_data.set_for_sale(price, listing_type, payment_mint);
+    save_nft_data(nft_data_info, &nft_data)?;
 
-    // For simplicity, we'll just log the listing
     msg!("NFT listed for sale successfully");
-    msg!("Price: {} lamports", price);
+    let currency = match payment_mint {
+        Some(payment_mint) => format!("units of {}", payment_mint),
+        None => "lamports".to_string(),
+    };
+    match &nft_data.listing_type {
+        ListingType::FixedPrice => msg!("Price: {} {}", price, currency),
+        ListingType::DecliningPrice { floor_price, end_at, .. } => msg!(
+            "Price: {} {}, declining to {} by {}",
+            price,
+            currency,
+            floor_price,
+            end_at
+        ),
+    }
 
     Ok(())
 }
@@ -594,22 +1298,55 @@ fn process_cancel_nft_listing(
     // Get accounts
     let account_info_iter = &mut accounts.iter();
     let owner_info = next_account_info(account_info_iter)?;
-    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
     let nft_mint_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
 
     // Verify the owner is a signer
     if !owner_info.is_signer {
         return Err(OtakuVerseError::Unauthorized.into());
     }
 
-    // In a real implementation, we would:
-    // 1. Fetch the NFT data from the account
-    // 2. Verify the owner is the actual owner of the NFT
-    // 3. Remove the NFT from sale
-    // 4. Store the updated NFT data
-    // NOTE: If you encounter stack overflow errors, box large arrays/structs to reduce stack usage.
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if !nft_data.is_for_sale {
+        return Err(OtakuVerseError::NotListed.into());
+    }
+    if nft_data.owner != *owner_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+
+    let escrow_bump = check_escrow_accounts(
+        program_id,
+        nft_mint_info.key,
+        escrow_authority_info,
+        escrow_token_info,
+    )?;
+
+    let owner_token = unpack_token_account(owner_token_info)?;
+    if owner_token.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if owner_token.owner != *owner_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+
+    // Return the NFT and close the escrow token account
+    release_from_escrow(
+        nft_mint_info.key,
+        escrow_token_info,
+        escrow_authority_info,
+        owner_token_info,
+        owner_info,
+        token_program_info,
+        escrow_bump,
+    )?;
+
+    nft_data.remove_from_sale();
+    save_nft_data(nft_data_info, &nft_data)?;
 
-    // For simplicity, we'll just log the cancellation
     msg!("NFT listing cancelled successfully");
 
     Ok(())
@@ -621,6 +1358,7 @@ fn process_send_community_message(
     accounts: &[AccountInfo],
     community_id: String,
     content: String,
+    reply_to: Option<u64>,
 ) -> ProgramResult {
     msg!("Processing SendCommunityMessage instruction");
 
@@ -630,6 +1368,8 @@ fn process_send_community_message(
     let community_info = next_account_info(account_info_iter)?;
     let message_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
+    let membership_info = next_account_info(account_info_iter)?;
+    let ban_info = next_account_info(account_info_iter)?;
 
     // Verify the sender is a signer
     if !sender_info.is_signer {
@@ -637,29 +1377,3582 @@ fn process_send_community_message(
     }
 
     // Verify the community exists
-    let community_data = CommunityData::unpack_from_slice(&community_info.data.borrow())
-        .map_err(|_| OtakuVerseError::CommunityNotFound)?;
+    let mut community_data = load_community(program_id, community_info)?;
 
     if community_data.id != community_id {
         return Err(OtakuVerseError::CommunityNotFound.into());
     }
 
+    // Only members who are not banned may post
+    load_membership(program_id, membership_info, community_info.key, sender_info.key)?;
+    check_not_banned(program_id, ban_info, community_info.key, sender_info.key)?;
+
+    if content.is_empty() {
+        return Err(OtakuVerseError::EmptyMessage.into());
+    }
+    if content.len() > MAX_MESSAGE_LEN {
+        return Err(OtakuVerseError::StringTooLong.into());
+    }
+
+    // The message is stored at the community's next index
+    let message_index = community_data.message_count;
+    let (message_pda, message_bump) =
+        MessageData::find_address(program_id, community_info.key, message_index);
+    if message_pda != *message_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    // Count the reply on its parent and join the parent's thread
+    let thread_root = match reply_to {
+        Some(parent_index) => {
+            let parent_info = next_account_info(account_info_iter)?;
+            let mut parent_data = load_message(program_id, parent_info, community_info.key)
+                .map_err(|_| OtakuVerseError::InvalidReplyTarget)?;
+            if parent_data.index != parent_index {
+                return Err(OtakuVerseError::InvalidReplyTarget.into());
+            }
+            parent_data.reply_count = parent_data.reply_count.saturating_add(1);
+            parent_data.pack_into_slice(&mut parent_info.data.borrow_mut())?;
+            Some(parent_data.thread_root.unwrap_or(parent_index))
+        }
+        None => None,
+    };
+
     // Get the current timestamp
     let clock = Clock::get()?;
     let current_timestamp = clock.unix_timestamp;
 
     // Create message data
     let message_data = MessageData {
-        
         community_id,
+        index: message_index,
         sender: *sender_info.key,
         content,
         sent_at: current_timestamp,
+        is_hidden: false,
+        reply_to,
+        thread_root,
+        reply_count: 0,
+        reactions: vec![],
+    };
+    let data = message_data.to_packed_vec()?;
+
+    create_pda_account(
+        program_id,
+        sender_info,
+        message_info,
+        system_program_info,
+        &Rent::get()?,
+        data.len(),
+        &[
+            MESSAGE_SEED,
+            community_info.key.as_ref(),
+            &message_index.to_le_bytes(),
+            &[message_bump],
+        ],
+    )?;
+    message_info.data.borrow_mut().copy_from_slice(&data);
+
+    community_data.message_count = message_index
+        .checked_add(1)
+        .ok_or(OtakuVerseError::MathOverflow)?;
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!(
+        "Message sent successfully: community {} index {}",
+        community_data.id,
+        message_index
+    );
+
+    Ok(())
+}
+
+/// Process AddReaction instruction
+fn process_add_reaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    emoji_code: u32,
+) -> ProgramResult {
+    msg!("Processing AddReaction instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let user_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let message_info = next_account_info(account_info_iter)?;
+    let reaction_info = next_account_info(account_info_iter)?;
+    let membership_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the user is a signing member
+    if !user_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    load_community(program_id, community_info)?;
+    load_membership(program_id, membership_info, community_info.key, user_info.key)?;
+
+    let mut message_data = load_message(program_id, message_info, community_info.key)?;
+
+    // One reaction per (message, user, emoji)
+    let (reaction_pda, reaction_bump) =
+        ReactionData::find_address(program_id, message_info.key, user_info.key, emoji_code);
+    if reaction_pda != *reaction_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !reaction_info.data_is_empty() {
+        return Err(OtakuVerseError::ReactionAlreadyExists.into());
+    }
+
+    if !message_data.add_reaction(emoji_code) {
+        return Err(OtakuVerseError::TooManyReactions.into());
+    }
+
+    let rent = Rent::get()?;
+    create_pda_account(
+        program_id,
+        user_info,
+        reaction_info,
+        system_program_info,
+        &rent,
+        ReactionData::LEN,
+        &[
+            REACTION_SEED,
+            message_info.key.as_ref(),
+            user_info.key.as_ref(),
+            &emoji_code.to_le_bytes(),
+            &[reaction_bump],
+        ],
+    )?;
+
+    let reaction_data = ReactionData {
+        message: *message_info.key,
+        user: *user_info.key,
+        emoji_code,
+        reacted_at: Clock::get()?.unix_timestamp,
+    };
+    reaction_data.pack_into_slice(&mut reaction_info.data.borrow_mut())?;
+
+    // A new emoji grows the message's tally list
+    let data = message_data.to_packed_vec()?;
+    if data.len() > message_info.data_len() {
+        resize_account(message_info, user_info, system_program_info, data.len())?;
+    }
+    message_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+
+    msg!("Reaction {} added to message {}", emoji_code, message_data.index);
+
+    Ok(())
+}
+
+/// Process RemoveReaction instruction
+fn process_remove_reaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    emoji_code: u32,
+) -> ProgramResult {
+    msg!("Processing RemoveReaction instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let user_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let message_info = next_account_info(account_info_iter)?;
+    let reaction_info = next_account_info(account_info_iter)?;
+
+    // Verify the user is a signer
+    if !user_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    load_community_or_closed(program_id, community_info)?;
+
+    // A deleted message leaves its reactions behind, which can still be closed
+    let message_data = if message_info.data_is_empty() {
+        None
+    } else {
+        Some(load_message(program_id, message_info, community_info.key)?)
+    };
+
+    let (reaction_pda, _) =
+        ReactionData::find_address(program_id, message_info.key, user_info.key, emoji_code);
+    if reaction_pda != *reaction_info.key || reaction_info.owner != program_id {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    close_program_account(reaction_info, user_info)?;
+
+    if let Some(mut message_data) = message_data {
+        message_data.remove_reaction(emoji_code);
+        message_data.pack_into_slice(&mut message_info.data.borrow_mut())?;
+    }
+
+    msg!("Reaction {} removed from message {}", emoji_code, message_info.key);
+
+    Ok(())
+}
+
+/// Process CreateCommunity instruction
+fn process_create_community(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: String,
+    name: String,
+    description: String,
+) -> ProgramResult {
+    msg!("Processing CreateCommunity instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let creator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the creator is a signer
+    if !creator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if id.is_empty() {
+        return Err(OtakuVerseError::InvalidInstructionData.into());
+    }
+    if id.len() > MAX_COMMUNITY_ID_LEN || !CommunityData::is_valid_details(&name, &description) {
+        return Err(OtakuVerseError::StringTooLong.into());
+    }
+
+    // The PDA is derived from the ID, so an existing account means the ID is taken
+    let (community_pda, community_bump) = CommunityData::find_address(program_id, &id);
+    if community_pda != *community_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !community_info.data_is_empty() {
+        return Err(OtakuVerseError::CommunityAlreadyExists.into());
+    }
+
+    create_pda_account(
+        program_id,
+        creator_info,
+        community_info,
+        system_program_info,
+        &Rent::get()?,
+        CommunityData::LEN,
+        &[COMMUNITY_SEED, id.as_bytes(), &[community_bump]],
+    )?;
+
+    let community_data = CommunityData {
+        id,
+        name,
+        description,
+        creator: *creator_info.key,
+        created_at: Clock::get()?.unix_timestamp,
+        message_count: 0,
+        member_count: 0,
+        moderators: vec![],
+    };
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("Community created successfully: {}", community_data.id);
+
+    Ok(())
+}
+
+/// Process UpdateCommunity instruction
+fn process_update_community(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: Option<String>,
+    description: Option<String>,
+) -> ProgramResult {
+    msg!("Processing UpdateCommunity instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let creator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+
+    let mut community_data = load_community(program_id, community_info)?;
+    if !creator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if community_data.creator != *creator_info.key {
+        return Err(OtakuVerseError::NotCommunityCreator.into());
+    }
+
+    if let Some(name) = name {
+        community_data.name = name;
+    }
+    if let Some(description) = description {
+        community_data.description = description;
+    }
+    if !CommunityData::is_valid_details(&community_data.name, &community_data.description) {
+        return Err(OtakuVerseError::StringTooLong.into());
+    }
+
+    // Clear stale bytes from a longer previous value before writing
+    community_info.data.borrow_mut().fill(0);
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("Community updated successfully: {}", community_data.id);
+
+    Ok(())
+}
+
+/// Process CloseCommunity instruction
+fn process_close_community(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Processing CloseCommunity instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let creator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+
+    let community_data = load_community(program_id, community_info)?;
+    if !creator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if community_data.creator != *creator_info.key {
+        return Err(OtakuVerseError::NotCommunityCreator.into());
+    }
+
+    // Shrink the account to a tombstone rather than closing it, since the community's
+    // messages, memberships and bans would come back if the ID were created again
+    let closed_data = ClosedCommunityData {
+        id: community_data.id.clone(),
+        closed_at: Clock::get()?.unix_timestamp,
+    };
+    community_info.realloc(ClosedCommunityData::LEN, false)?;
+    closed_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    let surplus = community_info
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(ClosedCommunityData::LEN));
+    transfer_lamports(community_info, creator_info, surplus)?;
+
+    msg!("Community closed successfully: {}", community_data.id);
+
+    Ok(())
+}
+
+/// Process JoinCommunity instruction
+fn process_join_community(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Processing JoinCommunity instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let user_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let membership_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let ban_info = next_account_info(account_info_iter)?;
+
+    // Verify the user is a signer
+    if !user_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut community_data = load_community(program_id, community_info)?;
+    check_not_banned(program_id, ban_info, community_info.key, user_info.key)?;
+
+    let (membership_pda, membership_bump) =
+        MembershipData::find_address(program_id, community_info.key, user_info.key);
+    if membership_pda != *membership_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !membership_info.data_is_empty() {
+        return Err(OtakuVerseError::AlreadyMember.into());
+    }
+
+    create_pda_account(
+        program_id,
+        user_info,
+        membership_info,
+        system_program_info,
+        &Rent::get()?,
+        MembershipData::LEN,
+        &[
+            MEMBERSHIP_SEED,
+            community_info.key.as_ref(),
+            user_info.key.as_ref(),
+            &[membership_bump],
+        ],
+    )?;
+
+    let membership_data = MembershipData {
+        community: *community_info.key,
+        member: *user_info.key,
+        joined_at: Clock::get()?.unix_timestamp,
     };
+    membership_data.pack_into_slice(&mut membership_info.data.borrow_mut())?;
+
+    community_data.member_count = community_data
+        .member_count
+        .checked_add(1)
+        .ok_or(OtakuVerseError::MathOverflow)?;
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
 
-    // Store message data (in a real implementation, this would be stored on-chain)
-    // For simplicity, we're just logging it here
-    msg!("Message sent successfully: {:?}", message_data);
+    msg!("Joined community: {}", community_data.id);
 
     Ok(())
 }
+
+/// Process LeaveCommunity instruction
+fn process_leave_community(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Processing LeaveCommunity instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let user_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let membership_info = next_account_info(account_info_iter)?;
+
+    // Verify the user is a signer
+    if !user_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let community_data = load_community_or_closed(program_id, community_info)?;
+    load_membership(program_id, membership_info, community_info.key, user_info.key)?;
+
+    close_program_account(membership_info, user_info)?;
+
+    // A closed community no longer counts its members
+    if let Some(mut community_data) = community_data {
+        community_data.member_count = community_data.member_count.saturating_sub(1);
+        community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+    }
+
+    msg!("Left community: {}", community_info.key);
+
+    Ok(())
+}
+
+/// Process AddModerator instruction
+fn process_add_moderator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    moderator: Pubkey,
+) -> ProgramResult {
+    msg!("Processing AddModerator instruction");
+
+    let (community_info, mut community_data) = load_community_as_creator(program_id, accounts)?;
+
+    if community_data.moderators.contains(&moderator) {
+        return Err(OtakuVerseError::ModeratorAlreadyExists.into());
+    }
+    if !community_data.add_moderator(moderator) {
+        return Err(OtakuVerseError::ModeratorListFull.into());
+    }
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("Moderator added to {}: {}", community_data.id, moderator);
+
+    Ok(())
+}
+
+/// Process RemoveModerator instruction
+fn process_remove_moderator(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    moderator: Pubkey,
+) -> ProgramResult {
+    msg!("Processing RemoveModerator instruction");
+
+    let (community_info, mut community_data) = load_community_as_creator(program_id, accounts)?;
+
+    if !community_data.remove_moderator(&moderator) {
+        return Err(OtakuVerseError::ModeratorNotFound.into());
+    }
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("Moderator removed from {}: {}", community_data.id, moderator);
+
+    Ok(())
+}
+
+/// Process TransferCommunityOwnership instruction
+fn process_transfer_community_ownership(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_creator: Pubkey,
+) -> ProgramResult {
+    msg!("Processing TransferCommunityOwnership instruction");
+
+    let (community_info, mut community_data) = load_community_as_creator(program_id, accounts)?;
+
+    // The creator moderates implicitly, so drop any redundant moderator entry
+    community_data.remove_moderator(&new_creator);
+    community_data.creator = new_creator;
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("Community {} transferred to {}", community_data.id, new_creator);
+
+    Ok(())
+}
+
+/// Process SetMessageHidden instruction
+fn process_set_message_hidden(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hidden: bool,
+) -> ProgramResult {
+    msg!("Processing SetMessageHidden instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let moderator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let message_info = next_account_info(account_info_iter)?;
+
+    let community_data = load_community(program_id, community_info)?;
+    if !moderator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if !community_data.can_moderate(moderator_info.key) {
+        return Err(OtakuVerseError::NotModerator.into());
+    }
+
+    let mut message_data = load_message(program_id, message_info, community_info.key)?;
+    message_data.is_hidden = hidden;
+    message_data.pack_into_slice(&mut message_info.data.borrow_mut())?;
+
+    msg!(
+        "Message {} in {} hidden: {}",
+        message_data.index,
+        community_data.id,
+        hidden
+    );
+
+    Ok(())
+}
+
+/// Process DeleteMessage instruction
+fn process_delete_message(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    msg!("Processing DeleteMessage instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let moderator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let message_info = next_account_info(account_info_iter)?;
+    let sender_info = next_account_info(account_info_iter)?;
+
+    let community_data = load_community_or_closed(program_id, community_info)?;
+    if !moderator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    // Anyone may clean up a closed community, since the rent only goes back to the sender
+    if let Some(community_data) = &community_data {
+        if !community_data.can_moderate(moderator_info.key) {
+            return Err(OtakuVerseError::NotModerator.into());
+        }
+    }
+
+    let message_data = load_message(program_id, message_info, community_info.key)?;
+    if message_data.sender != *sender_info.key {
+        return Err(OtakuVerseError::InvalidMessageData.into());
+    }
+
+    // The sender paid the rent, so it goes back to them
+    close_program_account(message_info, sender_info)?;
+
+    msg!("Message {} in {} deleted", message_data.index, community_info.key);
+
+    Ok(())
+}
+
+/// Process BanMember instruction
+fn process_ban_member(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user: Pubkey,
+) -> ProgramResult {
+    msg!("Processing BanMember instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let moderator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let ban_info = next_account_info(account_info_iter)?;
+    let membership_info = next_account_info(account_info_iter)?;
+    let user_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut community_data = load_community(program_id, community_info)?;
+    if !moderator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if !community_data.can_moderate(moderator_info.key) {
+        return Err(OtakuVerseError::NotModerator.into());
+    }
+
+    // Nobody can ban the creator, and only the creator can ban a moderator
+    if user == community_data.creator
+        || (community_data.moderators.contains(&user)
+            && community_data.creator != *moderator_info.key)
+    {
+        return Err(OtakuVerseError::CannotBan.into());
+    }
+    if *user_info.key != user {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (ban_pda, ban_bump) = BanData::find_address(program_id, community_info.key, &user);
+    if ban_pda != *ban_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !ban_info.data_is_empty() {
+        return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        program_id,
+        moderator_info,
+        ban_info,
+        system_program_info,
+        &Rent::get()?,
+        BanData::LEN,
+        &[BAN_SEED, community_info.key.as_ref(), user.as_ref(), &[ban_bump]],
+    )?;
+
+    let ban_data = BanData {
+        community: *community_info.key,
+        user,
+        banned_by: *moderator_info.key,
+        banned_at: Clock::get()?.unix_timestamp,
+    };
+    ban_data.pack_into_slice(&mut ban_info.data.borrow_mut())?;
+
+    // Remove the user's membership if they have one
+    let (membership_pda, _) = MembershipData::find_address(program_id, community_info.key, &user);
+    if membership_pda != *membership_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !membership_info.data_is_empty() {
+        load_membership(program_id, membership_info, community_info.key, &user)?;
+        close_program_account(membership_info, user_info)?;
+        community_data.member_count = community_data.member_count.saturating_sub(1);
+    }
+    community_data.remove_moderator(&user);
+    community_data.pack_into_slice(&mut community_info.data.borrow_mut())?;
+
+    msg!("User {} banned from {}", user, community_data.id);
+
+    Ok(())
+}
+
+/// Process UnbanMember instruction
+fn process_unban_member(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    user: Pubkey,
+) -> ProgramResult {
+    msg!("Processing UnbanMember instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let moderator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+    let ban_info = next_account_info(account_info_iter)?;
+    let banned_by_info = next_account_info(account_info_iter)?;
+
+    let community_data = load_community_or_closed(program_id, community_info)?;
+    if !moderator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    // Anyone may clean up a closed community, since the rent only goes back to the moderator
+    if let Some(community_data) = &community_data {
+        if !community_data.can_moderate(moderator_info.key) {
+            return Err(OtakuVerseError::NotModerator.into());
+        }
+    }
+
+    let (ban_pda, _) = BanData::find_address(program_id, community_info.key, &user);
+    if ban_pda != *ban_info.key || ban_info.owner != program_id {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    // The rent goes back to whoever paid for the ban record
+    let ban_data = BanData::unpack_from_slice(&ban_info.data.borrow())?;
+    if ban_data.banned_by != *banned_by_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    close_program_account(ban_info, banned_by_info)?;
+
+    msg!("User {} unbanned from {}", user, community_info.key);
+
+    Ok(())
+}
+
+/// Process InitializeConfig instruction
+fn process_initialize_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Pubkey,
+    platform_fee_basis_points: u16,
+) -> ProgramResult {
+    msg!("Processing InitializeConfig instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+
+    // Verify the authority is a signer and the program's upgrade authority, so nobody
+    // can front-run the deployment and take over the config
+    if !authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if upgrade_authority(program_id, program_data_info)? != Some(*authority_info.key) {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if platform_fee_basis_points > 10000 {
+        return Err(OtakuVerseError::InvalidBasisPoints.into());
+    }
+
+    let (config_pda, config_bump) = ProgramConfig::find_address(program_id);
+    if config_pda != *config_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !config_info.data_is_empty() {
+        return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+    }
+
+    let config = ProgramConfig {
+        authority: *authority_info.key,
+        treasury,
+        platform_fee_basis_points,
+        admins: vec![],
+        payment_mints: vec![],
+    };
+
+    create_pda_account(
+        program_id,
+        authority_info,
+        config_info,
+        system_program_info,
+        &Rent::get()?,
+        ProgramConfig::LEN,
+        &[CONFIG_SEED, &[config_bump]],
+    )?;
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Program config initialized");
+
+    Ok(())
+}
+
+/// Process UpdateConfig instruction
+fn process_update_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    treasury: Option<Pubkey>,
+    platform_fee_basis_points: Option<u16>,
+) -> ProgramResult {
+    msg!("Processing UpdateConfig instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    let mut config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer || config.authority != *authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if let Some(treasury) = treasury {
+        config.treasury = treasury;
+    }
+    if let Some(platform_fee_basis_points) = platform_fee_basis_points {
+        if platform_fee_basis_points > 10000 {
+            return Err(OtakuVerseError::InvalidBasisPoints.into());
+        }
+        config.platform_fee_basis_points = platform_fee_basis_points;
+    }
+
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Program config updated");
+
+    Ok(())
+}
+
+/// Process AddAdmin instruction
+fn process_add_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+) -> ProgramResult {
+    msg!("Processing AddAdmin instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    let mut config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer || config.authority != *authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if config.is_admin(&admin) {
+        return Err(OtakuVerseError::AdminAlreadyExists.into());
+    }
+    if !config.add_admin(admin) {
+        return Err(OtakuVerseError::AdminListFull.into());
+    }
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Admin added: {}", admin);
+
+    Ok(())
+}
+
+/// Process RemoveAdmin instruction
+fn process_remove_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+) -> ProgramResult {
+    msg!("Processing RemoveAdmin instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    let mut config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer || config.authority != *authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if !config.remove_admin(&admin) {
+        return Err(OtakuVerseError::AdminNotFound.into());
+    }
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Admin removed: {}", admin);
+
+    Ok(())
+}
+
+/// Process MigrateNFTData instruction
+fn process_migrate_nft_data(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Processing MigrateNFTData instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the payer is a signer
+    if !payer_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    let stored_version = NFTData::stored_version(&nft_data_info.data.borrow());
+    if stored_version == NFTData::VERSION {
+        msg!("NFT data is already at version {}", stored_version);
+        return Ok(());
+    }
+
+    // Rewrite the record with the current header, growing the account to fit
+    let data = nft_data.to_packed_vec()?;
+    let new_len = data.len().max(NFTData::LEN);
+    if new_len > nft_data_info.data_len() {
+        resize_account(nft_data_info, payer_info, system_program_info, new_len)?;
+    }
+    nft_data_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+
+    msg!(
+        "NFT data migrated from version {} to {}",
+        stored_version,
+        NFTData::VERSION
+    );
+
+    Ok(())
+}
+
+/// Process SetRaritySupplyCaps instruction
+fn process_set_rarity_supply_caps(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    anime_title: String,
+    caps: [Option<u64>; NFTRarity::COUNT],
+) -> ProgramResult {
+    msg!("Processing SetRaritySupplyCaps instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let rarity_supply_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the admin is a signing OtakuVerse admin
+    let config = load_config(program_id, config_info)?;
+    if !admin_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if !config.is_admin(admin_info.key) {
+        return Err(OtakuVerseError::NotAdmin.into());
+    }
+
+    if anime_title.len() > MAX_ANIME_TITLE_LEN {
+        return Err(OtakuVerseError::AnimeTitleTooLong.into());
+    }
+
+    let mut rarity_supply = load_or_create_rarity_supply(
+        program_id,
+        admin_info,
+        rarity_supply_info,
+        system_program_info,
+        &anime_title,
+    )?;
+    rarity_supply.set_caps(caps)?;
+    rarity_supply.pack_into_slice(&mut rarity_supply_info.data.borrow_mut())?;
+
+    msg!("Rarity supply caps set for {}: {:?}", anime_title, caps);
+
+    Ok(())
+}
+
+/// Process CreateCollection instruction
+fn process_create_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    name: String,
+    symbol: String,
+    uri: String,
+) -> ProgramResult {
+    msg!("Processing CreateCollection instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let collection_mint_info = next_account_info(account_info_iter)?;
+    let collection_token_info = next_account_info(account_info_iter)?;
+    let collection_metadata_info = next_account_info(account_info_iter)?;
+    let collection_master_edition_info = next_account_info(account_info_iter)?;
+    let collection_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    // Verify the authority is a signer
+    if !authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if *token_metadata_program_info.key != mpl_token_metadata::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if name.len() > MAX_NAME_LEN {
+        return Err(OtakuVerseError::NameTooLong.into());
+    }
+    if symbol.len() > MAX_SYMBOL_LEN {
+        return Err(OtakuVerseError::SymbolTooLong.into());
+    }
+    if uri.len() > MAX_URI_LEN {
+        return Err(OtakuVerseError::UriTooLong.into());
+    }
+
+    // Verify the collection account is the mint's PDA and not yet created
+    let (collection_pda, collection_bump) =
+        CollectionData::find_address(program_id, collection_mint_info.key);
+    if collection_pda != *collection_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !collection_info.data_is_empty() {
+        return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+    }
+    let collection_seeds: &[&[u8]] = &[
+        COLLECTION_SEED,
+        collection_mint_info.key.as_ref(),
+        &[collection_bump],
+    ];
+
+    // Create and initialize the collection mint
+    let rent = Rent::from_account_info(rent_info)?;
+    invoke(
+        &system_instruction::create_account(
+            authority_info.key,
+            collection_mint_info.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            spl_token::id(),
+        ),
+        &[authority_info.clone(), collection_mint_info.clone()],
+    )?;
+    invoke(
+        &token_instruction::initialize_mint(
+            spl_token::id(),
+            collection_mint_info.key,
+            authority_info.key,
+            Some(authority_info.key),
+            0,
+        )?,
+        &[
+            collection_mint_info.clone(),
+            rent_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // Mint the collection NFT to the authority
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            authority_info.key,
+            authority_info.key,
+            collection_mint_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            authority_info.clone(),
+            collection_token_info.clone(),
+            authority_info.clone(),
+            collection_mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &token_instruction::mint_to(
+            spl_token::id(),
+            collection_mint_info.key,
+            collection_token_info.key,
+            authority_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            collection_mint_info.clone(),
+            collection_token_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // Create the sized collection metadata, with the collection PDA as update authority
+    invoke(
+        &metadata_instruction::create_metadata_accounts_v3(
+            mpl_token_metadata::id(),
+            *collection_metadata_info.key,
+            *collection_mint_info.key,
+            *authority_info.key,
+            *authority_info.key,
+            *collection_info.key,
+            name.clone(),
+            symbol,
+            uri,
+            None,
+            0,
+            false,
+            true,
+            None,
+            None,
+            Some(CollectionDetails::V1 { size: 0 }),
+        ),
+        &[
+            collection_metadata_info.clone(),
+            collection_mint_info.clone(),
+            authority_info.clone(),
+            collection_info.clone(),
+            system_program_info.clone(),
+            rent_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+    )?;
+
+    // Lock the supply at one with a master edition
+    invoke_signed(
+        &metadata_instruction::create_master_edition_v3(
+            mpl_token_metadata::id(),
+            *collection_master_edition_info.key,
+            *collection_mint_info.key,
+            *collection_info.key,
+            *authority_info.key,
+            *collection_metadata_info.key,
+            *authority_info.key,
+            Some(0),
+        ),
+        &[
+            collection_master_edition_info.clone(),
+            collection_mint_info.clone(),
+            collection_info.clone(),
+            authority_info.clone(),
+            collection_metadata_info.clone(),
+            token_program_info.clone(),
+            system_program_info.clone(),
+            rent_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+        &[collection_seeds],
+    )?;
+
+    let collection = CollectionData {
+        mint: *collection_mint_info.key,
+        authority: *authority_info.key,
+        name,
+        size: 0,
+        created_at: Clock::get()?.unix_timestamp,
+    };
+    create_pda_account(
+        program_id,
+        authority_info,
+        collection_info,
+        system_program_info,
+        &rent,
+        CollectionData::LEN,
+        collection_seeds,
+    )?;
+    collection.pack_into_slice(&mut collection_info.data.borrow_mut())?;
+
+    msg!("Collection created: {}", collection.mint);
+
+    Ok(())
+}
+
+/// Process EditNFTAttributes instruction
+fn process_edit_nft_attributes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    remove: Vec<String>,
+    set: Vec<NFTAttribute>,
+    uri: Option<String>,
+) -> ProgramResult {
+    msg!("Processing EditNFTAttributes instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let update_authority_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the update authority is a signer
+    if !update_authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if *token_metadata_program_info.key != mpl_token_metadata::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Only the token metadata update authority may change the NFT
+    let metadata = Metadata::from_account_info(nft_metadata_info)?;
+    if metadata.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if metadata.update_authority != *update_authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    for trait_type in &remove {
+        if !nft_data.remove_attribute(trait_type) {
+            return Err(OtakuVerseError::AttributeNotFound.into());
+        }
+    }
+    for attribute in set {
+        nft_data.set_attribute(attribute);
+    }
+    if let Some(uri) = uri {
+        nft_data.uri = uri;
+    }
+    nft_data.validate()?;
+
+    // Grow records created before the current layout to the full size
+    if nft_data_info.data_len() < NFTData::LEN {
+        resize_account(
+            nft_data_info,
+            update_authority_info,
+            system_program_info,
+            NFTData::LEN,
+        )?;
+    }
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    update_token_metadata(
+        &nft_data,
+        metadata,
+        nft_metadata_info,
+        update_authority_info,
+        token_metadata_program_info,
+    )?;
+
+    msg!("NFT attributes updated: {}", nft_data.mint);
+
+    Ok(())
+}
+
+/// Process MakeOffer instruction
+fn process_make_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+    expires_at: i64,
+) -> ProgramResult {
+    msg!("Processing MakeOffer instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let buyer_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let offer_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the buyer is a signer
+    if !buyer_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if price == 0 {
+        return Err(OtakuVerseError::InvalidPrice.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if expires_at <= now {
+        return Err(OtakuVerseError::InvalidExpiry.into());
+    }
+
+    // Only transferable OtakuVerse NFTs can receive offers
+    let nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if nft_data.is_soulbound {
+        return Err(OtakuVerseError::SoulboundNFT.into());
+    }
+
+    // Verify the offer account is the buyer's PDA for this mint and not yet created
+    let (offer_pda, offer_bump) =
+        OfferData::find_address(program_id, nft_mint_info.key, buyer_info.key);
+    if offer_pda != *offer_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !offer_info.data_is_empty() {
+        return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        program_id,
+        buyer_info,
+        offer_info,
+        system_program_info,
+        &Rent::get()?,
+        OfferData::LEN,
+        &[
+            OFFER_SEED,
+            nft_mint_info.key.as_ref(),
+            buyer_info.key.as_ref(),
+            &[offer_bump],
+        ],
+    )?;
+
+    // Escrow the offered lamports in the offer account
+    invoke(
+        &system_instruction::transfer(buyer_info.key, offer_info.key, price),
+        &[
+            buyer_info.clone(),
+            offer_info.clone(),
+            system_program_info.clone(),
+        ],
+    )?;
+
+    let offer = OfferData {
+        mint: *nft_mint_info.key,
+        buyer: *buyer_info.key,
+        price,
+        created_at: now,
+        expires_at,
+    };
+    offer.pack_into_slice(&mut offer_info.data.borrow_mut())?;
+
+    msg!("Offer of {} lamports made for {}", price, offer.mint);
+
+    Ok(())
+}
+
+/// Process AcceptOffer instruction
+fn process_accept_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    price: u64,
+) -> ProgramResult {
+    msg!("Processing AcceptOffer instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    let buyer_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let offer_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let buyer_token_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let creator_infos = account_info_iter.as_slice();
+
+    // Verify the owner is a signer
+    if !owner_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    // Verify the offer
+    let offer = load_offer(program_id, offer_info, nft_mint_info.key, buyer_info.key)?;
+    if offer.price != price {
+        return Err(OtakuVerseError::PriceMismatch.into());
+    }
+    if offer.is_expired(Clock::get()?.unix_timestamp) {
+        return Err(OtakuVerseError::OfferExpired.into());
+    }
+
+    let config = load_config(program_id, config_info)?;
+    if config.treasury != *treasury_info.key {
+        return Err(OtakuVerseError::TreasuryMismatch.into());
+    }
+
+    let buyer_token = unpack_token_account(buyer_token_info)?;
+    if buyer_token.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if buyer_token.owner != *buyer_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+
+    // Deliver the NFT to the buyer, from escrow if it is listed
+    if nft_data.is_for_sale {
+        if nft_data.owner != *owner_info.key {
+            return Err(OtakuVerseError::NotOwner.into());
+        }
+        let escrow_bump = check_escrow_accounts(
+            program_id,
+            nft_mint_info.key,
+            escrow_authority_info,
+            escrow_token_info,
+        )?;
+        release_from_escrow(
+            nft_mint_info.key,
+            escrow_token_info,
+            escrow_authority_info,
+            buyer_token_info,
+            owner_info,
+            token_program_info,
+            escrow_bump,
+        )?;
+    } else {
+        check_token_holder(owner_token_info, nft_mint_info.key, owner_info.key)?;
+        invoke(
+            &token_instruction::transfer(
+                spl_token::id(),
+                owner_token_info.key,
+                buyer_token_info.key,
+                owner_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                owner_token_info.clone(),
+                buyer_token_info.clone(),
+                owner_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
+
+    // Pay creators, platform and owner out of the offer, then refund its rent
+    let payment = split_sale_payment(
+        &nft_data,
+        &config,
+        price,
+        creator_infos,
+        treasury_info,
+        owner_info,
+        None,
+    )?;
+    for (receiver_info, amount) in payment.payouts() {
+        transfer_lamports(offer_info, receiver_info, amount)?;
+    }
+    close_program_account(offer_info, buyer_info)?;
+
+    nft_data.transfer(*buyer_info.key);
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    msg!("Offer of {} lamports accepted for {}", price, nft_data.mint);
+    msg!(
+        "Royalty: {} lamports, platform fee: {} lamports",
+        payment.royalty_total(),
+        payment.platform_fee
+    );
+
+    Ok(())
+}
+
+/// Process CancelOffer instruction
+fn process_cancel_offer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Processing CancelOffer instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let buyer_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let offer_info = next_account_info(account_info_iter)?;
+
+    // Verify the buyer is a signer
+    if !buyer_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let offer = load_offer(program_id, offer_info, nft_mint_info.key, buyer_info.key)?;
+
+    // Refund the offered lamports along with the rent
+    close_program_account(offer_info, buyer_info)?;
+
+    msg!("Offer of {} lamports cancelled for {}", offer.price, offer.mint);
+
+    Ok(())
+}
+
+/// Process CreateAuction instruction
+fn process_create_auction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    reserve_price: u64,
+    min_bid_increment: u64,
+    start_at: i64,
+    end_at: i64,
+    extension_seconds: i64,
+) -> ProgramResult {
+    msg!("Processing CreateAuction instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let seller_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let seller_token_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the seller is a signer
+    if !seller_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if reserve_price == 0 || min_bid_increment == 0 {
+        return Err(OtakuVerseError::InvalidPrice.into());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if end_at <= start_at || end_at <= now || extension_seconds < 0 {
+        return Err(OtakuVerseError::InvalidAuctionSchedule.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if nft_data.is_for_sale {
+        return Err(OtakuVerseError::AlreadyListed.into());
+    }
+    if nft_data.is_soulbound {
+        return Err(OtakuVerseError::SoulboundNFT.into());
+    }
+
+    // Verify the signer holds the NFT
+    check_token_holder(seller_token_info, nft_mint_info.key, seller_info.key)?;
+
+    check_escrow_accounts(
+        program_id,
+        nft_mint_info.key,
+        escrow_authority_info,
+        escrow_token_info,
+    )?;
+
+    // Verify the auction account is the mint's PDA and not yet created
+    let (auction_pda, auction_bump) = AuctionData::find_address(program_id, nft_mint_info.key);
+    if auction_pda != *auction_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !auction_info.data_is_empty() {
+        return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+    }
+
+    create_pda_account(
+        program_id,
+        seller_info,
+        auction_info,
+        system_program_info,
+        &Rent::get()?,
+        AuctionData::LEN,
+        &[AUCTION_SEED, nft_mint_info.key.as_ref(), &[auction_bump]],
+    )?;
+
+    // Move the NFT into escrow for the duration of the auction
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            seller_info.key,
+            escrow_authority_info.key,
+            nft_mint_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            seller_info.clone(),
+            escrow_token_info.clone(),
+            escrow_authority_info.clone(),
+            nft_mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &token_instruction::transfer(
+            spl_token::id(),
+            seller_token_info.key,
+            escrow_token_info.key,
+            seller_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            seller_token_info.clone(),
+            escrow_token_info.clone(),
+            seller_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // The token account is the source of truth for ownership
+    nft_data.owner = *seller_info.key;
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    let auction = AuctionData {
+        mint: *nft_mint_info.key,
+        seller: *seller_info.key,
+        reserve_price,
+        min_bid_increment,
+        start_at,
+        end_at,
+        extension_seconds,
+        highest_bidder: None,
+        highest_bid: 0,
+    };
+    auction.pack_into_slice(&mut auction_info.data.borrow_mut())?;
+
+    msg!("Auction created for {}", auction.mint);
+    msg!("Reserve price: {} lamports, ends at {}", reserve_price, end_at);
+
+    Ok(())
+}
+
+/// Process PlaceBid instruction
+fn process_place_bid(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    msg!("Processing PlaceBid instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let bidder_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let previous_bidder_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the bidder is a signer
+    if !bidder_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut auction = load_auction(program_id, auction_info, nft_mint_info.key)?;
+    if auction.seller == *bidder_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let now = Clock::get()?.unix_timestamp;
+    if !auction.is_active(now) {
+        return Err(OtakuVerseError::AuctionNotActive.into());
+    }
+    let min_bid = auction.min_next_bid().ok_or(OtakuVerseError::MathOverflow)?;
+    if amount < min_bid {
+        return Err(OtakuVerseError::BidTooLow.into());
+    }
+
+    // Escrow the new bid in the auction account
+    invoke(
+        &system_instruction::transfer(bidder_info.key, auction_info.key, amount),
+        &[
+            bidder_info.clone(),
+            auction_info.clone(),
+            system_program_info.clone(),
+        ],
+    )?;
+
+    // Refund the bid it outbids
+    if let Some(previous_bidder) = auction.highest_bidder {
+        if previous_bidder != *previous_bidder_info.key {
+            return Err(OtakuVerseError::BidderMismatch.into());
+        }
+        transfer_lamports(auction_info, previous_bidder_info, auction.highest_bid)?;
+    }
+
+    auction.record_bid(*bidder_info.key, amount, now);
+    auction.pack_into_slice(&mut auction_info.data.borrow_mut())?;
+
+    msg!("Bid of {} lamports placed on {}", amount, auction.mint);
+    msg!("Auction ends at {}", auction.end_at);
+
+    Ok(())
+}
+
+/// Process SettleAuction instruction
+fn process_settle_auction(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Processing SettleAuction instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let seller_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let auction_info = next_account_info(account_info_iter)?;
+    let escrow_token_info = next_account_info(account_info_iter)?;
+    let escrow_authority_info = next_account_info(account_info_iter)?;
+    let destination_token_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let creator_infos = account_info_iter.as_slice();
+
+    // Anyone may settle an ended auction; the proceeds and NFT only go to their recipients
+    let auction = load_auction(program_id, auction_info, nft_mint_info.key)?;
+    if auction.seller != *seller_info.key {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+    if Clock::get()?.unix_timestamp < auction.end_at {
+        return Err(OtakuVerseError::AuctionNotEnded.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    // The NFT goes to the winner, or back to the seller if nobody bid
+    let recipient = auction.highest_bidder.unwrap_or(auction.seller);
+    let destination_token = unpack_token_account(destination_token_info)?;
+    if destination_token.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if destination_token.owner != recipient {
+        return Err(OtakuVerseError::BidderMismatch.into());
+    }
+
+    let escrow_bump = check_escrow_accounts(
+        program_id,
+        nft_mint_info.key,
+        escrow_authority_info,
+        escrow_token_info,
+    )?;
+    release_from_escrow(
+        nft_mint_info.key,
+        escrow_token_info,
+        escrow_authority_info,
+        destination_token_info,
+        seller_info,
+        token_program_info,
+        escrow_bump,
+    )?;
+
+    // Pay creators, platform and seller out of the winning bid, then refund the rent
+    if auction.highest_bidder.is_some() {
+        let config = load_config(program_id, config_info)?;
+        if config.treasury != *treasury_info.key {
+            return Err(OtakuVerseError::TreasuryMismatch.into());
+        }
+
+        let payment = split_sale_payment(
+            &nft_data,
+            &config,
+            auction.highest_bid,
+            creator_infos,
+            treasury_info,
+            seller_info,
+            None,
+        )?;
+        for (receiver_info, amount) in payment.payouts() {
+            transfer_lamports(auction_info, receiver_info, amount)?;
+        }
+
+        msg!(
+            "Royalty: {} lamports, platform fee: {} lamports",
+            payment.royalty_total(),
+            payment.platform_fee
+        );
+    }
+    close_program_account(auction_info, seller_info)?;
+
+    nft_data.transfer(recipient);
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    match auction.highest_bidder {
+        Some(winner) => msg!(
+            "Auction for {} won by {} for {} lamports",
+            auction.mint,
+            winner,
+            auction.highest_bid
+        ),
+        None => msg!("Auction for {} ended without bids", auction.mint),
+    }
+
+    Ok(())
+}
+
+/// Process AddPaymentMint instruction
+fn process_add_payment_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    msg!("Processing AddPaymentMint instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer || config.authority != *authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if config.accepts_payment_mint(&mint) {
+        return Err(OtakuVerseError::PaymentMintAlreadyAccepted.into());
+    }
+    if !config.add_payment_mint(mint) {
+        return Err(OtakuVerseError::PaymentMintListFull.into());
+    }
+
+    // Configs created before the allowlist existed are too small for a full one
+    if config_info.data_len() < ProgramConfig::LEN {
+        resize_account(config_info, authority_info, system_program_info, ProgramConfig::LEN)?;
+    }
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Payment mint accepted: {}", mint);
+
+    Ok(())
+}
+
+/// Process RemovePaymentMint instruction
+fn process_remove_payment_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+) -> ProgramResult {
+    msg!("Processing RemovePaymentMint instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+
+    let mut config = load_config(program_id, config_info)?;
+    if !authority_info.is_signer || config.authority != *authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    if !config.remove_payment_mint(&mint) {
+        return Err(OtakuVerseError::PaymentMintNotAccepted.into());
+    }
+    config.pack_into_slice(&mut config_info.data.borrow_mut())?;
+
+    msg!("Payment mint removed: {}", mint);
+
+    Ok(())
+}
+
+/// Process CreateMasterEdition instruction
+fn process_create_master_edition(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_supply: Option<u64>,
+) -> ProgramResult {
+    msg!("Processing CreateMasterEdition instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let update_authority_info = next_account_info(account_info_iter)?;
+    let mint_authority_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let master_edition_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+
+    // Verify the update authority is a signer
+    if !update_authority_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    // Only the token metadata update authority may create the master edition
+    let metadata = Metadata::from_account_info(nft_metadata_info)?;
+    if metadata.mint != *nft_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if metadata.update_authority != *update_authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if nft_data.edition.is_some() {
+        return Err(OtakuVerseError::AlreadyAnEdition.into());
+    }
+
+    invoke(
+        &metadata_instruction::create_master_edition_v3(
+            *token_metadata_program_info.key,
+            *master_edition_info.key,
+            *nft_mint_info.key,
+            *update_authority_info.key,
+            *mint_authority_info.key,
+            *nft_metadata_info.key,
+            *update_authority_info.key,
+            max_supply,
+        ),
+        &[
+            master_edition_info.clone(),
+            nft_mint_info.clone(),
+            update_authority_info.clone(),
+            mint_authority_info.clone(),
+            nft_metadata_info.clone(),
+            token_program_info.clone(),
+            system_program_info.clone(),
+            rent_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+    )?;
+
+    nft_data.edition = Some(NFTEdition::Master { max_supply });
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    msg!("Master edition created for {}", nft_data.mint);
+    match max_supply {
+        Some(max_supply) => msg!("Max supply: {}", max_supply),
+        None => msg!("Max supply: unlimited"),
+    }
+
+    Ok(())
+}
+
+/// Process PrintEdition instruction
+fn process_print_edition(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    edition: u64,
+) -> ProgramResult {
+    msg!("Processing PrintEdition instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let holder_info = next_account_info(account_info_iter)?;
+    let print_mint_info = next_account_info(account_info_iter)?;
+    let print_token_info = next_account_info(account_info_iter)?;
+    let print_metadata_info = next_account_info(account_info_iter)?;
+    let print_edition_info = next_account_info(account_info_iter)?;
+    let print_data_info = next_account_info(account_info_iter)?;
+    let master_mint_info = next_account_info(account_info_iter)?;
+    let master_data_info = next_account_info(account_info_iter)?;
+    let master_metadata_info = next_account_info(account_info_iter)?;
+    let master_edition_info = next_account_info(account_info_iter)?;
+    let edition_marker_info = next_account_info(account_info_iter)?;
+    let master_token_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let rarity_supply_info = next_account_info(account_info_iter)?;
+
+    // Verify the holder is a signer
+    if !holder_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    // Verify the master edition and that the signer holds it
+    let master_data = load_nft_data(program_id, master_data_info, master_mint_info.key)?;
+    if !matches!(master_data.edition, Some(NFTEdition::Master { .. })) {
+        return Err(OtakuVerseError::NotMasterEdition.into());
+    }
+    check_token_holder(master_token_info, master_mint_info.key, holder_info.key)?;
+
+    // Prints keep the master's update authority
+    let master_metadata = Metadata::from_account_info(master_metadata_info)?;
+    if master_metadata.mint != *master_mint_info.key {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if master_metadata.update_authority != *update_authority_info.key {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    // Verify the print's NFT data account is its mint's PDA and not yet created
+    let (print_data_pda, print_data_bump) = NFTData::find_address(program_id, print_mint_info.key);
+    if print_data_pda != *print_data_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if !print_data_info.data_is_empty() {
+        return Err(OtakuVerseError::NFTAlreadyExists.into());
+    }
+
+    // The print shares the master's design and royalties. Token metadata copies the
+    // master's collection to the print unverified, so the print is not a collection item
+    let mut print_data = Box::new(NFTData::new_with_details(
+        *print_mint_info.key,
+        *holder_info.key,
+        master_data.name.clone(),
+        master_data.symbol.clone(),
+        master_data.uri.clone(),
+        master_data.anime_title.clone(),
+        master_data.rarity.clone(),
+        master_data.creator,
+        master_data.description.clone(),
+        None,
+        master_data.attributes.clone(),
+        master_data.royalty_basis_points,
+        master_data.creators.clone(),
+        Clock::get()?.unix_timestamp,
+    ));
+    print_data.edition = Some(NFTEdition::Print {
+        master_mint: *master_mint_info.key,
+        number: edition,
+    });
+    print_data.validate()?;
+
+    // Every print counts against the anime's supply cap for the master's rarity
+    let mut rarity_supply =
+        load_rarity_supply(program_id, rarity_supply_info, &print_data.anime_title)?;
+    rarity_supply.record_mint(&print_data.rarity)?;
+    rarity_supply.pack_into_slice(&mut rarity_supply_info.data.borrow_mut())?;
+
+    // Create and initialize the print mint with the single token
+    let rent = Rent::from_account_info(rent_info)?;
+    invoke(
+        &system_instruction::create_account(
+            holder_info.key,
+            print_mint_info.key,
+            rent.minimum_balance(spl_token::state::Mint::LEN),
+            spl_token::state::Mint::LEN as u64,
+            spl_token::id(),
+        ),
+        &[holder_info.clone(), print_mint_info.clone()],
+    )?;
+    invoke(
+        &token_instruction::initialize_mint(
+            spl_token::id(),
+            print_mint_info.key,
+            holder_info.key,
+            Some(holder_info.key),
+            0,
+        )?,
+        &[
+            print_mint_info.clone(),
+            rent_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account(
+            holder_info.key,
+            holder_info.key,
+            print_mint_info.key,
+        ),
+        &[
+            holder_info.clone(),
+            print_token_info.clone(),
+            holder_info.clone(),
+            print_mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+    invoke(
+        &token_instruction::mint_to(
+            spl_token::id(),
+            print_mint_info.key,
+            print_token_info.key,
+            holder_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            print_mint_info.clone(),
+            print_token_info.clone(),
+            holder_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // Turn the mint into the numbered print, which also creates its metadata
+    invoke(
+        &metadata_instruction::mint_new_edition_from_master_edition_via_token(
+            *token_metadata_program_info.key,
+            *print_metadata_info.key,
+            *print_edition_info.key,
+            *master_edition_info.key,
+            *print_mint_info.key,
+            *holder_info.key,
+            *holder_info.key,
+            *holder_info.key,
+            *master_token_info.key,
+            *update_authority_info.key,
+            *master_metadata_info.key,
+            *master_mint_info.key,
+            edition,
+        ),
+        &[
+            print_metadata_info.clone(),
+            print_edition_info.clone(),
+            master_edition_info.clone(),
+            print_mint_info.clone(),
+            edition_marker_info.clone(),
+            holder_info.clone(),
+            master_token_info.clone(),
+            update_authority_info.clone(),
+            master_metadata_info.clone(),
+            token_program_info.clone(),
+            system_program_info.clone(),
+            rent_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+    )?;
+
+    store_new_nft_data(
+        program_id,
+        holder_info,
+        print_data_info,
+        system_program_info,
+        &rent,
+        &print_data,
+        print_data_bump,
+    )?;
+
+    msg!("Edition {} of {} printed: {}", edition, master_data.mint, print_data.mint);
+
+    Ok(())
+}
+
+/// Process BurnNFT instruction
+fn process_burn_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    redeem: bool,
+) -> ProgramResult {
+    msg!("Processing BurnNFT instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let nft_metadata_info = next_account_info(account_info_iter)?;
+    let nft_edition_info = next_account_info(account_info_iter)?;
+    let redemption_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let token_metadata_program_info = next_account_info(account_info_iter)?;
+    let freeze_authority_info = next_account_info(account_info_iter)?;
+    let extra_infos = account_info_iter.as_slice();
+
+    // Verify the owner is a signer
+    if !owner_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+
+    // Verify the signer holds the NFT, so listed or auctioned NFTs cannot be burned
+    check_token_holder(owner_token_info, nft_mint_info.key, owner_info.key)?;
+
+    // Soulbound tokens are frozen and closable only by the freeze authority, so thaw them
+    // and hand the close authority back to the owner before they can be burned
+    let (freeze_authority, freeze_bump) =
+        NFTData::find_freeze_authority(program_id, nft_mint_info.key);
+    let freeze_seeds: &[&[u8]] = &[SOULBOUND_SEED, nft_mint_info.key.as_ref(), &[freeze_bump]];
+    let soulbound_signer = [freeze_seeds];
+    let mint_authority_seeds: &[&[&[u8]]] =
+        if nft_data.is_soulbound { &soulbound_signer } else { &[] };
+    if nft_data.is_soulbound {
+        if freeze_authority != *freeze_authority_info.key {
+            return Err(OtakuVerseError::InvalidPDA.into());
+        }
+
+        invoke_signed(
+            &token_instruction::thaw_account(
+                spl_token::id(),
+                owner_token_info.key,
+                nft_mint_info.key,
+                freeze_authority_info.key,
+                &[],
+            )?,
+            &[
+                owner_token_info.clone(),
+                nft_mint_info.clone(),
+                freeze_authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[freeze_seeds],
+        )?;
+        invoke_signed(
+            &token_instruction::set_authority(
+                spl_token::id(),
+                owner_token_info.key,
+                None,
+                token_instruction::AuthorityType::CloseAccount,
+                freeze_authority_info.key,
+                &[],
+            )?,
+            &[
+                owner_token_info.clone(),
+                freeze_authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[freeze_seeds],
+        )?;
+    }
+
+    // Collection items pass the collection and its metadata ahead of any edition accounts
+    let (collection_infos, edition_infos) = match (nft_data.collection_id, extra_infos) {
+        (Some(_), [collection_info, collection_metadata_info, edition_infos @ ..]) => {
+            (Some((collection_info, collection_metadata_info)), edition_infos)
+        }
+        (Some(_), _) => return Err(ProgramError::NotEnoughAccountKeys),
+        (None, _) => (None, extra_infos),
+    };
+    let collection_metadata_info = collection_infos.map(|(_, metadata_info)| metadata_info);
+
+    // Burn the token through token metadata, closing the token account, the metadata and
+    // the edition
+    match &nft_data.edition {
+        Some(NFTEdition::Master { .. }) | None => {
+            // Token metadata only closes the metadata of NFTs with a master edition, so
+            // older NFTs minted without one get an edition that allows no prints first
+            if nft_data.edition.is_none() {
+                let (update_authority_info, mint_authority_info) =
+                    match (nft_data.is_soulbound, edition_infos) {
+                        (true, [update_authority_info, ..]) => {
+                            (update_authority_info, freeze_authority_info)
+                        }
+                        (false, [update_authority_info, mint_authority_info, ..]) => {
+                            (update_authority_info, mint_authority_info)
+                        }
+                        _ => return Err(ProgramError::NotEnoughAccountKeys),
+                    };
+
+                invoke_signed(
+                    &metadata_instruction::create_master_edition_v3(
+                        *token_metadata_program_info.key,
+                        *nft_edition_info.key,
+                        *nft_mint_info.key,
+                        *update_authority_info.key,
+                        *mint_authority_info.key,
+                        *nft_metadata_info.key,
+                        *owner_info.key,
+                        Some(0),
+                    ),
+                    &[
+                        nft_edition_info.clone(),
+                        nft_mint_info.clone(),
+                        update_authority_info.clone(),
+                        mint_authority_info.clone(),
+                        owner_info.clone(),
+                        nft_metadata_info.clone(),
+                        token_program_info.clone(),
+                        system_program_info.clone(),
+                        token_metadata_program_info.clone(),
+                    ],
+                    mint_authority_seeds,
+                )?;
+            }
+
+            let mut burn_infos = vec![
+                nft_metadata_info.clone(),
+                owner_info.clone(),
+                nft_mint_info.clone(),
+                owner_token_info.clone(),
+                nft_edition_info.clone(),
+                token_program_info.clone(),
+                token_metadata_program_info.clone(),
+            ];
+            burn_infos.extend(collection_metadata_info.cloned());
+
+            invoke(
+                &metadata_instruction::burn_nft(
+                    *token_metadata_program_info.key,
+                    *nft_metadata_info.key,
+                    *owner_info.key,
+                    *nft_mint_info.key,
+                    *owner_token_info.key,
+                    *nft_edition_info.key,
+                    spl_token::id(),
+                    collection_metadata_info.map(|info| *info.key),
+                ),
+                &burn_infos,
+            )?;
+        }
+        Some(NFTEdition::Print { master_mint, .. }) => {
+            let [
+                master_mint_info,
+                master_token_info,
+                master_edition_info,
+                edition_marker_info,
+                ..,
+            ] = edition_infos
+            else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            if master_mint_info.key != master_mint {
+                return Err(OtakuVerseError::EditionMismatch.into());
+            }
+
+            invoke(
+                &metadata_instruction::burn_edition_nft(
+                    *token_metadata_program_info.key,
+                    *nft_metadata_info.key,
+                    *owner_info.key,
+                    *nft_mint_info.key,
+                    *master_mint_info.key,
+                    *owner_token_info.key,
+                    *master_token_info.key,
+                    *master_edition_info.key,
+                    *nft_edition_info.key,
+                    *edition_marker_info.key,
+                    spl_token::id(),
+                ),
+                &[
+                    nft_metadata_info.clone(),
+                    owner_info.clone(),
+                    nft_mint_info.clone(),
+                    master_mint_info.clone(),
+                    owner_token_info.clone(),
+                    master_token_info.clone(),
+                    master_edition_info.clone(),
+                    nft_edition_info.clone(),
+                    edition_marker_info.clone(),
+                    token_program_info.clone(),
+                    token_metadata_program_info.clone(),
+                ],
+            )?;
+        }
+    }
+
+    // The burned NFT no longer counts towards its collection
+    if let (Some(collection_mint), Some((collection_info, _))) =
+        (nft_data.collection_id, collection_infos)
+    {
+        let (mut collection, _) = load_collection(program_id, collection_info, &collection_mint)?;
+        collection.remove_item()?;
+        collection.pack_into_slice(&mut collection_info.data.borrow_mut())?;
+    }
+
+    // Record the redemption before the NFT data is gone
+    if redeem {
+        let (redemption_pda, redemption_bump) =
+            RedemptionData::find_address(program_id, nft_mint_info.key, owner_info.key);
+        if redemption_pda != *redemption_info.key {
+            return Err(OtakuVerseError::InvalidPDA.into());
+        }
+        if !redemption_info.data_is_empty() {
+            return Err(OtakuVerseError::AccountAlreadyInitialized.into());
+        }
+
+        create_pda_account(
+            program_id,
+            owner_info,
+            redemption_info,
+            system_program_info,
+            &Rent::get()?,
+            RedemptionData::LEN,
+            &[
+                REDEMPTION_SEED,
+                nft_mint_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[redemption_bump],
+            ],
+        )?;
+
+        let redemption = RedemptionData {
+            mint: nft_data.mint,
+            owner: *owner_info.key,
+            name: nft_data.name.clone(),
+            anime_title: nft_data.anime_title.clone(),
+            rarity: nft_data.rarity.clone(),
+            redeemed_at: Clock::get()?.unix_timestamp,
+        };
+        redemption.pack_into_slice(&mut redemption_info.data.borrow_mut())?;
+
+        msg!("Redemption recorded for {}", redemption.mint);
+    }
+
+    close_program_account(nft_data_info, owner_info)?;
+
+    msg!("NFT burned: {}", nft_data.mint);
+
+    Ok(())
+}
+
+/// Process ReissueSoulboundNFT instruction
+fn process_reissue_soulbound_nft(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    msg!("Processing ReissueSoulboundNFT instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let holder_info = next_account_info(account_info_iter)?;
+    let new_wallet_info = next_account_info(account_info_iter)?;
+    let nft_mint_info = next_account_info(account_info_iter)?;
+    let nft_data_info = next_account_info(account_info_iter)?;
+    let holder_token_info = next_account_info(account_info_iter)?;
+    let new_wallet_token_info = next_account_info(account_info_iter)?;
+    let freeze_authority_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let associated_token_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the admin is a signer; the holder may have lost the old wallet
+    let config = load_config(program_id, config_info)?;
+    if !admin_info.is_signer || !config.is_admin(admin_info.key) {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+
+    let mut nft_data = load_nft_data(program_id, nft_data_info, nft_mint_info.key)?;
+    if !nft_data.is_soulbound {
+        return Err(OtakuVerseError::NotSoulbound.into());
+    }
+    check_token_holder(holder_token_info, nft_mint_info.key, holder_info.key)?;
+
+    let (freeze_authority, freeze_bump) =
+        NFTData::find_freeze_authority(program_id, nft_mint_info.key);
+    if freeze_authority != *freeze_authority_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    let new_wallet_token = spl_associated_token_account::get_associated_token_address(
+        new_wallet_info.key,
+        nft_mint_info.key,
+    );
+    if new_wallet_token != *new_wallet_token_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    let freeze_seeds: &[&[u8]] = &[SOULBOUND_SEED, nft_mint_info.key.as_ref(), &[freeze_bump]];
+
+    // Thaw the old token account, then burn the NFT and close the account as its
+    // delegate and close authority, refunding the rent to the holder
+    invoke_signed(
+        &token_instruction::thaw_account(
+            spl_token::id(),
+            holder_token_info.key,
+            nft_mint_info.key,
+            freeze_authority_info.key,
+            &[],
+        )?,
+        &[
+            holder_token_info.clone(),
+            nft_mint_info.clone(),
+            freeze_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[freeze_seeds],
+    )?;
+
+    invoke_signed(
+        &token_instruction::burn(
+            spl_token::id(),
+            holder_token_info.key,
+            nft_mint_info.key,
+            freeze_authority_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            holder_token_info.clone(),
+            nft_mint_info.clone(),
+            freeze_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[freeze_seeds],
+    )?;
+
+    invoke_signed(
+        &token_instruction::close_account(
+            spl_token::id(),
+            holder_token_info.key,
+            holder_info.key,
+            freeze_authority_info.key,
+            &[],
+        )?,
+        &[
+            holder_token_info.clone(),
+            holder_info.clone(),
+            freeze_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[freeze_seeds],
+    )?;
+
+    // Mint the NFT again into the new wallet
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            admin_info.key,
+            new_wallet_info.key,
+            nft_mint_info.key,
+            &spl_token::id(),
+        ),
+        &[
+            admin_info.clone(),
+            new_wallet_token_info.clone(),
+            new_wallet_info.clone(),
+            nft_mint_info.clone(),
+            system_program_info.clone(),
+            token_program_info.clone(),
+            associated_token_program_info.clone(),
+        ],
+    )?;
+
+    invoke_signed(
+        &token_instruction::mint_to(
+            spl_token::id(),
+            nft_mint_info.key,
+            new_wallet_token_info.key,
+            freeze_authority_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            nft_mint_info.clone(),
+            new_wallet_token_info.clone(),
+            freeze_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[freeze_seeds],
+    )?;
+
+    // A signing new wallet lets the freeze authority burn and close its token account,
+    // so the NFT can be reissued again without it
+    if new_wallet_info.is_signer {
+        invoke(
+            &token_instruction::approve(
+                spl_token::id(),
+                new_wallet_token_info.key,
+                freeze_authority_info.key,
+                new_wallet_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                new_wallet_token_info.clone(),
+                freeze_authority_info.clone(),
+                new_wallet_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+        invoke(
+            &token_instruction::set_authority(
+                spl_token::id(),
+                new_wallet_token_info.key,
+                Some(freeze_authority_info.key),
+                token_instruction::AuthorityType::CloseAccount,
+                new_wallet_info.key,
+                &[],
+            )?,
+            &[
+                new_wallet_token_info.clone(),
+                new_wallet_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
+
+    // Freeze the NFT again in the new wallet
+    invoke_signed(
+        &token_instruction::freeze_account(
+            spl_token::id(),
+            new_wallet_token_info.key,
+            nft_mint_info.key,
+            freeze_authority_info.key,
+            &[],
+        )?,
+        &[
+            new_wallet_token_info.clone(),
+            nft_mint_info.clone(),
+            freeze_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[freeze_seeds],
+    )?;
+
+    nft_data.transfer(*new_wallet_info.key);
+    save_nft_data(nft_data_info, &nft_data)?;
+
+    msg!("Soulbound NFT {} reissued to {}", nft_data.mint, new_wallet_info.key);
+
+    Ok(())
+}
+
+/// Process MigrateAccount instruction
+fn process_migrate_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_type: LegacyAccountType,
+) -> ProgramResult {
+    msg!("Processing MigrateAccount instruction");
+
+    // Get accounts
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    // Verify the payer is a signer
+    if !payer_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if account_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    // Every record must sit at the PDA derived from its contents, so an account
+    // cannot be rewritten as a different type
+    match account_type {
+        LegacyAccountType::Config => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            ProgramConfig::LEN,
+            |_: &ProgramConfig| ProgramConfig::find_address(program_id).0,
+        ),
+        LegacyAccountType::Community => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            CommunityData::LEN,
+            |community: &CommunityData| CommunityData::find_address(program_id, &community.id).0,
+        ),
+        LegacyAccountType::Message => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            0,
+            |message: &MessageData| {
+                let (community, _) = CommunityData::find_address(program_id, &message.community_id);
+                MessageData::find_address(program_id, &community, message.index).0
+            },
+        ),
+        LegacyAccountType::Reaction => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            ReactionData::LEN,
+            |reaction: &ReactionData| {
+                ReactionData::find_address(
+                    program_id,
+                    &reaction.message,
+                    &reaction.user,
+                    reaction.emoji_code,
+                )
+                .0
+            },
+        ),
+        LegacyAccountType::Membership => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            MembershipData::LEN,
+            |membership: &MembershipData| {
+                MembershipData::find_address(program_id, &membership.community, &membership.member)
+                    .0
+            },
+        ),
+        LegacyAccountType::Ban => migrate_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            BanData::LEN,
+            |ban: &BanData| BanData::find_address(program_id, &ban.community, &ban.user).0,
+        ),
+    }
+}
+
+/// Create the NFT data PDA for a freshly minted NFT and write its record
+fn store_new_nft_data<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    nft_data_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    rent: &Rent,
+    nft_data: &NFTData,
+    bump: u8,
+) -> ProgramResult {
+    let data = nft_data.to_packed_vec()?;
+
+    create_pda_account(
+        program_id,
+        payer_info,
+        nft_data_info,
+        system_program_info,
+        rent,
+        NFTData::LEN,
+        &[NFT_DATA_SEED, nft_data.mint.as_ref(), &[bump]],
+    )?;
+
+    nft_data_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+
+    Ok(())
+}
+
+/// Rewrite a record at the current layout version, growing the account to at
+/// least `min_len`
+///
+/// `find_address` derives the PDA the record must be stored at.
+fn migrate_account<'a, T: VersionedAccount>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    min_len: usize,
+    find_address: impl FnOnce(&T) -> Pubkey,
+) -> ProgramResult {
+    let record = T::unpack_from_slice(&account_info.data.borrow())?;
+    if find_address(&record) != *account_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    let stored_version = T::stored_version(&account_info.data.borrow());
+    if stored_version == T::VERSION {
+        msg!("Account is already at version {}", stored_version);
+        return Ok(());
+    }
+
+    let data = record.to_packed_vec()?;
+    let new_len = data.len().max(min_len);
+    if new_len > account_info.data_len() {
+        resize_account(account_info, payer_info, system_program_info, new_len)?;
+    }
+    account_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+
+    msg!("Account migrated from version {} to {}", stored_version, T::VERSION);
+
+    Ok(())
+}
+
+/// Write an NFT's name, symbol, URI and royalty to its token metadata, keeping the
+/// metadata's creators, collection and uses
+fn update_token_metadata<'a>(
+    nft_data: &NFTData,
+    metadata: Metadata,
+    nft_metadata_info: &AccountInfo<'a>,
+    update_authority_info: &AccountInfo<'a>,
+    token_metadata_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    invoke(
+        &metadata_instruction::update_metadata_accounts_v2(
+            mpl_token_metadata::id(),
+            *nft_metadata_info.key,
+            *update_authority_info.key,
+            None,
+            Some(DataV2 {
+                name: nft_data.name.clone(),
+                symbol: nft_data.symbol.clone(),
+                uri: nft_data.uri.clone(),
+                seller_fee_basis_points: nft_data.royalty_basis_points,
+                creators: metadata.data.creators,
+                collection: metadata.collection,
+                uses: metadata.uses,
+            }),
+            None,
+            None,
+        ),
+        &[
+            nft_metadata_info.clone(),
+            update_authority_info.clone(),
+            token_metadata_program_info.clone(),
+        ],
+    )
+}
+
+/// Read the upgrade authority from a program's ProgramData account
+fn upgrade_authority(
+    program_id: &Pubkey,
+    program_data_info: &AccountInfo,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let (program_data, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data != *program_data_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if *program_data_info.owner != bpf_loader_upgradeable::id() {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    // Bincode layout of `UpgradeableLoaderState::ProgramData`: a u32 variant index (3),
+    // the u64 deployment slot, then the optional upgrade authority
+    let data = program_data_info.data.borrow();
+    let metadata = data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if metadata[..4] != 3u32.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    match metadata[12] {
+        0 => Ok(None),
+        1 => Pubkey::try_from(&metadata[13..45])
+            .map(Some)
+            .map_err(|_| ProgramError::InvalidAccountData),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Grow a program-owned account to `new_len`, topping up rent from the payer
+fn resize_account<'a>(
+    account_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    new_len: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(new_len);
+    let top_up = required_lamports.saturating_sub(account_info.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, top_up),
+            &[
+                payer_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    account_info.realloc(new_len, false)
+}
+
+/// Create a program-owned PDA account funded by the payer
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    pda_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    rent: &Rent,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_info.key,
+            pda_info.key,
+            rent.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[
+            payer_info.clone(),
+            pda_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[signer_seeds],
+    )
+}
+
+/// Load the NFT data record for a mint, checking it is the program-owned PDA
+fn load_nft_data(
+    program_id: &Pubkey,
+    nft_data_info: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<NFTData, ProgramError> {
+    if nft_data_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let (nft_data_pda, _) = NFTData::find_address(program_id, mint);
+    if nft_data_pda != *nft_data_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    let nft_data = NFTData::unpack_from_slice(&nft_data_info.data.borrow())?;
+    if nft_data.mint != *mint {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+
+    Ok(nft_data)
+}
+
+/// Write an NFT data record back to its account
+///
+/// Records still at an older, smaller layout must go through `MigrateNFTData`
+/// before they can be rewritten with the current header.
+fn save_nft_data(nft_data_info: &AccountInfo, nft_data: &NFTData) -> ProgramResult {
+    let data = nft_data.to_packed_vec()?;
+    if data.len() > nft_data_info.data_len() {
+        return Err(OtakuVerseError::AccountNeedsMigration.into());
+    }
+    nft_data_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+    Ok(())
+}
+
+/// How a sale price is divided between the NFT's creators, the platform and the seller
+struct SalePayment<'a, 'b> {
+    /// Each creator's account and royalty
+    royalties: Vec<(&'b AccountInfo<'a>, u64)>,
+    treasury_info: &'b AccountInfo<'a>,
+    platform_fee: u64,
+    seller_info: &'b AccountInfo<'a>,
+    seller_proceeds: u64,
+}
+
+impl<'a, 'b> SalePayment<'a, 'b> {
+    /// Every receiving account with the lamports it is owed
+    fn payouts(&self) -> Vec<(&'b AccountInfo<'a>, u64)> {
+        let mut payouts = self.royalties.clone();
+        payouts.push((self.treasury_info, self.platform_fee));
+        payouts.push((self.seller_info, self.seller_proceeds));
+        payouts
+    }
+
+    fn royalty_total(&self) -> u64 {
+        self.royalties.iter().map(|(_, amount)| amount).sum()
+    }
+}
+
+/// Split a sale price by the NFT's royalty shares and the platform fee, checking
+/// the creator accounts are the NFT's creators in order
+///
+/// With a payment mint, the creator accounts are the creators' token accounts for it.
+fn split_sale_payment<'a, 'b>(
+    nft_data: &NFTData,
+    config: &ProgramConfig,
+    price: u64,
+    creator_infos: &'b [AccountInfo<'a>],
+    treasury_info: &'b AccountInfo<'a>,
+    seller_info: &'b AccountInfo<'a>,
+    payment_mint: Option<&Pubkey>,
+) -> Result<SalePayment<'a, 'b>, ProgramError> {
+    let split = nft_data.royalty_split(price);
+    if creator_infos.len() != split.len() {
+        return Err(OtakuVerseError::CreatorMismatch.into());
+    }
+    let royalties = creator_infos
+        .iter()
+        .zip(split)
+        .map(|(creator_info, (creator, amount))| {
+            let is_creator = match payment_mint {
+                Some(payment_mint) => {
+                    check_payment_token_account(creator_info, payment_mint, &creator).is_ok()
+                }
+                None => *creator_info.key == creator,
+            };
+            if !is_creator {
+                return Err(OtakuVerseError::CreatorMismatch.into());
+            }
+            Ok((creator_info, amount))
+        })
+        .collect::<Result<Vec<_>, ProgramError>>()?;
+
+    let platform_fee = config.calculate_platform_fee(price);
+    let seller_proceeds = royalties
+        .iter()
+        .try_fold(price, |remaining, (_, amount)| remaining.checked_sub(*amount))
+        .and_then(|remaining| remaining.checked_sub(platform_fee))
+        .ok_or(OtakuVerseError::InsufficientFunds)?;
+
+    Ok(SalePayment {
+        royalties,
+        treasury_info,
+        platform_fee,
+        seller_info,
+        seller_proceeds,
+    })
+}
+
+/// Unpack an SPL token account owned by the token program
+fn unpack_token_account(
+    token_info: &AccountInfo,
+) -> Result<spl_token::state::Account, ProgramError> {
+    if *token_info.owner != spl_token::id() {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+    spl_token::state::Account::unpack(&token_info.data.borrow())
+}
+
+/// Verify a token account holds the single token of `mint` for `holder`
+fn check_token_holder(
+    token_info: &AccountInfo,
+    mint: &Pubkey,
+    holder: &Pubkey,
+) -> ProgramResult {
+    let token_account = unpack_token_account(token_info)?;
+    if token_account.mint != *mint {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if token_account.owner != *holder || token_account.amount != 1 {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+    Ok(())
+}
+
+/// Verify a token account holds tokens of the payment `mint` for `owner`
+fn check_payment_token_account(
+    token_info: &AccountInfo,
+    mint: &Pubkey,
+    owner: &Pubkey,
+) -> ProgramResult {
+    let token_account = unpack_token_account(token_info)?;
+    if token_account.mint != *mint {
+        return Err(OtakuVerseError::MintMismatch.into());
+    }
+    if token_account.owner != *owner {
+        return Err(OtakuVerseError::NotOwner.into());
+    }
+    Ok(())
+}
+
+/// Verify the escrow authority PDA and its token account, returning the PDA bump
+fn check_escrow_accounts(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    escrow_authority_info: &AccountInfo,
+    escrow_token_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (escrow_authority, escrow_bump) = NFTData::find_escrow_authority(program_id, mint);
+    if escrow_authority != *escrow_authority_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    let escrow_token =
+        spl_associated_token_account::get_associated_token_address(&escrow_authority, mint);
+    if escrow_token != *escrow_token_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    Ok(escrow_bump)
+}
+
+/// Transfer an escrowed NFT to `destination` and close the escrow token account,
+/// refunding its rent to `rent_receiver`
+fn release_from_escrow<'a>(
+    mint: &Pubkey,
+    escrow_token_info: &AccountInfo<'a>,
+    escrow_authority_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    rent_receiver_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    escrow_bump: u8,
+) -> ProgramResult {
+    let escrow_seeds: &[&[u8]] = &[ESCROW_SEED, mint.as_ref(), &[escrow_bump]];
+
+    invoke_signed(
+        &token_instruction::transfer(
+            spl_token::id(),
+            escrow_token_info.key,
+            destination_info.key,
+            escrow_authority_info.key,
+            &[],
+            1,
+        )?,
+        &[
+            escrow_token_info.clone(),
+            destination_info.clone(),
+            escrow_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[escrow_seeds],
+    )?;
+
+    invoke_signed(
+        &token_instruction::close_account(
+            spl_token::id(),
+            escrow_token_info.key,
+            rent_receiver_info.key,
+            escrow_authority_info.key,
+            &[],
+        )?,
+        &[
+            escrow_token_info.clone(),
+            rent_receiver_info.clone(),
+            escrow_authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[escrow_seeds],
+    )
+}
+
+/// Load the rarity supply counters for an anime, creating them if missing
+fn load_or_create_rarity_supply<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    rarity_supply_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    anime_title: &str,
+) -> Result<RaritySupplyData, ProgramError> {
+    let (rarity_supply_pda, bump) = RaritySupplyData::find_address(program_id, anime_title);
+    if rarity_supply_pda != *rarity_supply_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    if rarity_supply_info.data_is_empty() {
+        create_pda_account(
+            program_id,
+            payer_info,
+            rarity_supply_info,
+            system_program_info,
+            &Rent::get()?,
+            RaritySupplyData::LEN,
+            &[RARITY_SUPPLY_SEED, hash(anime_title.as_bytes()).as_ref(), &[bump]],
+        )?;
+        return Ok(RaritySupplyData::new(anime_title.to_string()));
+    }
+
+    if rarity_supply_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+    RaritySupplyData::unpack_from_slice(&rarity_supply_info.data.borrow())
+}
+
+/// Load an anime's rarity supply for a mint
+///
+/// Only SetRaritySupplyCaps creates the account, so nothing can be minted for an anime
+/// before an admin has capped its tiers.
+fn load_rarity_supply(
+    program_id: &Pubkey,
+    rarity_supply_info: &AccountInfo,
+    anime_title: &str,
+) -> Result<RaritySupplyData, ProgramError> {
+    let (rarity_supply_pda, _) = RaritySupplyData::find_address(program_id, anime_title);
+    if rarity_supply_pda != *rarity_supply_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if rarity_supply_info.data_is_empty() {
+        return Err(OtakuVerseError::RaritySupplyNotSet.into());
+    }
+    if rarity_supply_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    RaritySupplyData::unpack_from_slice(&rarity_supply_info.data.borrow())
+}
+
+/// Load a collection, checking it is the program-owned PDA for its mint
+fn load_collection(
+    program_id: &Pubkey,
+    collection_info: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<(CollectionData, u8), ProgramError> {
+    if collection_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let (collection_pda, bump) = CollectionData::find_address(program_id, mint);
+    if collection_pda != *collection_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    let collection = CollectionData::unpack_from_slice(&collection_info.data.borrow())?;
+    Ok((collection, bump))
+}
+
+/// Load a buyer's offer for a mint, checking it is the program-owned PDA
+fn load_offer(
+    program_id: &Pubkey,
+    offer_info: &AccountInfo,
+    mint: &Pubkey,
+    buyer: &Pubkey,
+) -> Result<OfferData, ProgramError> {
+    if offer_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let (offer_pda, _) = OfferData::find_address(program_id, mint, buyer);
+    if offer_pda != *offer_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    OfferData::unpack_from_slice(&offer_info.data.borrow())
+}
+
+/// Load the auction for a mint, checking it is the program-owned PDA
+fn load_auction(
+    program_id: &Pubkey,
+    auction_info: &AccountInfo,
+    mint: &Pubkey,
+) -> Result<AuctionData, ProgramError> {
+    if auction_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let (auction_pda, _) = AuctionData::find_address(program_id, mint);
+    if auction_pda != *auction_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    AuctionData::unpack_from_slice(&auction_info.data.borrow())
+}
+
+/// Load the program config, checking it is the program-owned PDA
+fn load_config(
+    program_id: &Pubkey,
+    config_info: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    if config_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let (config_pda, _) = ProgramConfig::find_address(program_id);
+    if config_pda != *config_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    ProgramConfig::unpack_from_slice(&config_info.data.borrow())
+}
+
+/// Load a community, checking it is the program-owned PDA for its ID
+fn load_community(
+    program_id: &Pubkey,
+    community_info: &AccountInfo,
+) -> Result<CommunityData, ProgramError> {
+    if community_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let community_data = CommunityData::unpack_from_slice(&community_info.data.borrow())
+        .map_err(|_| OtakuVerseError::CommunityNotFound)?;
+
+    let (community_pda, _) = CommunityData::find_address(program_id, &community_data.id);
+    if community_pda != *community_info.key {
+        return Err(OtakuVerseError::CommunityNotFound.into());
+    }
+
+    Ok(community_data)
+}
+
+/// Load a community that may have been closed, returning `None` for its tombstone
+///
+/// Only for paths that close a community's records and refund their rent; everything
+/// else uses [`load_community`] so closed communities cannot be used.
+fn load_community_or_closed(
+    program_id: &Pubkey,
+    community_info: &AccountInfo,
+) -> Result<Option<CommunityData>, ProgramError> {
+    if community_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let closed_data = ClosedCommunityData::unpack_from_slice(&community_info.data.borrow());
+    match closed_data {
+        Ok(closed_data) => {
+            let (community_pda, _) = CommunityData::find_address(program_id, &closed_data.id);
+            if community_pda != *community_info.key {
+                return Err(OtakuVerseError::CommunityNotFound.into());
+            }
+            Ok(None)
+        }
+        Err(_) => load_community(program_id, community_info).map(Some),
+    }
+}
+
+/// Load the community from `[signer] creator, [writable] community` accounts,
+/// checking the signer is its creator
+fn load_community_as_creator<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+) -> Result<(&'a AccountInfo<'b>, CommunityData), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    let creator_info = next_account_info(account_info_iter)?;
+    let community_info = next_account_info(account_info_iter)?;
+
+    let community_data = load_community(program_id, community_info)?;
+    if !creator_info.is_signer {
+        return Err(OtakuVerseError::Unauthorized.into());
+    }
+    if community_data.creator != *creator_info.key {
+        return Err(OtakuVerseError::NotCommunityCreator.into());
+    }
+
+    Ok((community_info, community_data))
+}
+
+/// Load a message, checking it is the program-owned PDA for its community and index
+fn load_message(
+    program_id: &Pubkey,
+    message_info: &AccountInfo,
+    community: &Pubkey,
+) -> Result<MessageData, ProgramError> {
+    if message_info.owner != program_id {
+        return Err(OtakuVerseError::WrongAccountOwner.into());
+    }
+
+    let message_data = MessageData::unpack_from_slice(&message_info.data.borrow())?;
+    let (message_pda, _) = MessageData::find_address(program_id, community, message_data.index);
+    if message_pda != *message_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+
+    Ok(message_data)
+}
+
+/// Verify the user has no ban record in the community
+fn check_not_banned(
+    program_id: &Pubkey,
+    ban_info: &AccountInfo,
+    community: &Pubkey,
+    user: &Pubkey,
+) -> ProgramResult {
+    let (ban_pda, _) = BanData::find_address(program_id, community, user);
+    if ban_pda != *ban_info.key {
+        return Err(OtakuVerseError::InvalidPDA.into());
+    }
+    if ban_info.owner == program_id && !ban_info.data_is_empty() {
+        return Err(OtakuVerseError::UserBanned.into());
+    }
+    Ok(())
+}
+
+/// Load a user's membership of a community, checking it is the program-owned PDA
+fn load_membership(
+    program_id: &Pubkey,
+    membership_info: &AccountInfo,
+    community: &Pubkey,
+    member: &Pubkey,
+) -> Result<MembershipData, ProgramError> {
+    let (membership_pda, _) = MembershipData::find_address(program_id, community, member);
+    if membership_pda != *membership_info.key || membership_info.owner != program_id {
+        return Err(OtakuVerseError::NotMember.into());
+    }
+
+    let membership_data = MembershipData::unpack_from_slice(&membership_info.data.borrow())?;
+    if membership_data.community != *community || membership_data.member != *member {
+        return Err(OtakuVerseError::NotMember.into());
+    }
+
+    Ok(membership_data)
+}
+
+/// Move lamports out of a program-owned account
+fn transfer_lamports(
+    source_info: &AccountInfo,
+    destination_info: &AccountInfo,
+    amount: u64,
+) -> ProgramResult {
+    **source_info.lamports.borrow_mut() = source_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(OtakuVerseError::InsufficientFunds)?;
+    **destination_info.lamports.borrow_mut() = destination_info
+        .lamports()
+        .checked_add(amount)
+        .ok_or(OtakuVerseError::MathOverflow)?;
+    Ok(())
+}
+
+/// Close a program-owned account, sending its lamports to `destination_info`
+fn close_program_account(
+    account_info: &AccountInfo,
+    destination_info: &AccountInfo,
+) -> ProgramResult {
+    let lamports = account_info.lamports();
+    **account_info.lamports.borrow_mut() = 0;
+    **destination_info.lamports.borrow_mut() = destination_info
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(OtakuVerseError::MathOverflow)?;
+
+    account_info.assign(&system_program::id());
+    account_info.realloc(0, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mpl_token_metadata::state::MAX_MASTER_EDITION_LEN;
+    use solana_program::{
+        entrypoint::{self, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+        instruction::Instruction,
+        program_pack::Pack,
+        program_stubs::{self, SyscallStubs},
+    };
+
+    /// Serves the clock and rent sysvars outside the runtime, and stands in for the
+    /// lamport movements of token metadata CPIs. SPL token CPIs are accepted as no-ops.
+    struct TestRuntime;
+
+    /// Token metadata instruction tags, as token metadata's Borsh version differs from ours
+    const CREATE_MASTER_EDITION_V3: u8 = 17;
+    const BURN_NFT: u8 = 29;
+
+    impl SyscallStubs for TestRuntime {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Clock) = Clock::default() };
+            SUCCESS
+        }
+
+        fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+            unsafe { *(var_addr as *mut Rent) = Rent::default() };
+            SUCCESS
+        }
+
+        fn sol_invoke_signed(
+            &self,
+            instruction: &Instruction,
+            account_infos: &[AccountInfo],
+            _signers_seeds: &[&[&[u8]]],
+        ) -> ProgramResult {
+            if instruction.program_id != mpl_token_metadata::id() {
+                return Ok(());
+            }
+            let account = |index: usize| {
+                let key = instruction.accounts[index].pubkey;
+                account_infos.iter().find(|info| *info.key == key).unwrap()
+            };
+            let move_lamports = |from: usize, to: usize, amount: u64| {
+                **account(from).lamports.borrow_mut() -= amount;
+                **account(to).lamports.borrow_mut() += amount;
+            };
+
+            match instruction.data[0] {
+                // The payer funds the edition account
+                CREATE_MASTER_EDITION_V3 => {
+                    move_lamports(4, 0, Rent::default().minimum_balance(MAX_MASTER_EDITION_LEN));
+                }
+                // The metadata, token and edition accounts are closed to the owner
+                BURN_NFT => {
+                    for index in [0, 3, 4] {
+                        move_lamports(index, 1, account(index).lamports());
+                    }
+                }
+                _ => unimplemented!(),
+            }
+            Ok(())
+        }
+    }
+
+    /// An account passed to a processor, rent exempt for its data
+    struct TestAccount {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+        is_signer: bool,
+    }
+
+    impl TestAccount {
+        fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>, is_signer: bool) -> Self {
+            let lamports = Rent::default().minimum_balance(data.len());
+            Self { key, owner, lamports, data, is_signer }
+        }
+
+        fn wallet(key: Pubkey) -> Self {
+            Self::new(key, system_program::id(), vec![], true)
+        }
+    }
+
+    /// Run a processor on the accounts serialized the way the runtime passes them, so
+    /// reallocs work, and copy the resulting account state back
+    fn run(
+        program_id: &Pubkey,
+        accounts: &mut [TestAccount],
+        process: impl FnOnce(&Pubkey, &[AccountInfo]) -> ProgramResult,
+    ) -> ProgramResult {
+        program_stubs::set_syscall_stubs(Box::new(TestRuntime));
+
+        let mut input = (accounts.len() as u64).to_le_bytes().to_vec();
+        for account in accounts.iter() {
+            // Not a duplicate, signer, writable, not executable, original data length
+            input.extend_from_slice(&[u8::MAX, account.is_signer as u8, 1, 0, 0, 0, 0, 0]);
+            input.extend_from_slice(account.key.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().next_multiple_of(8), 0);
+            input.extend_from_slice(&0u64.to_le_bytes());
+        }
+        input.extend_from_slice(&0u64.to_le_bytes());
+        input.extend_from_slice(program_id.as_ref());
+
+        // The runtime passes the input 8-byte aligned
+        let mut aligned = vec![0u64; input.len().div_ceil(8)];
+        let aligned_ptr = aligned.as_mut_ptr() as *mut u8;
+        let (_, account_infos, _) = unsafe {
+            std::ptr::copy_nonoverlapping(input.as_ptr(), aligned_ptr, input.len());
+            entrypoint::deserialize(aligned_ptr)
+        };
+
+        let result = process(program_id, &account_infos);
+        for (account, account_info) in accounts.iter_mut().zip(&account_infos) {
+            account.owner = *account_info.owner;
+            account.lamports = account_info.lamports();
+            account.data = account_info.data.borrow().to_vec();
+        }
+        result
+    }
+
+    #[test]
+    fn leave_closed_community() {
+        let program_id = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let member = Pubkey::new_unique();
+        let (community, _) = CommunityData::find_address(&program_id, "isekai");
+        let (membership, _) = MembershipData::find_address(&program_id, &community, &member);
+
+        let mut community_data = vec![0; CommunityData::LEN];
+        CommunityData {
+            id: "isekai".to_string(),
+            name: "Isekai".to_string(),
+            description: String::new(),
+            creator,
+            created_at: 0,
+            message_count: 0,
+            member_count: 1,
+            moderators: vec![],
+        }
+        .pack_into_slice(&mut community_data)
+        .unwrap();
+        let mut membership_data = vec![0; MembershipData::LEN];
+        MembershipData { community, member, joined_at: 0 }
+            .pack_into_slice(&mut membership_data)
+            .unwrap();
+
+        let mut accounts = [
+            TestAccount::wallet(creator),
+            TestAccount::new(community, program_id, community_data, false),
+        ];
+        run(&program_id, &mut accounts, process_close_community).unwrap();
+        let [_, community_account] = accounts;
+        assert!(ClosedCommunityData::unpack_from_slice(&community_account.data).is_ok());
+        assert_eq!(
+            community_account.lamports,
+            Rent::default().minimum_balance(ClosedCommunityData::LEN)
+        );
+
+        // The member can still leave and reclaim the membership rent
+        let mut accounts = [
+            TestAccount::wallet(member),
+            community_account,
+            TestAccount::new(membership, program_id, membership_data, false),
+        ];
+        let member_lamports = accounts[0].lamports;
+        let membership_rent = accounts[2].lamports;
+        run(&program_id, &mut accounts, process_leave_community).unwrap();
+
+        assert_eq!(accounts[0].lamports, member_lamports + membership_rent);
+        assert_eq!(accounts[2].lamports, 0);
+        assert_eq!(accounts[2].owner, system_program::id());
+        assert!(accounts[2].data.is_empty());
+        assert!(ClosedCommunityData::unpack_from_slice(&accounts[1].data).is_ok());
+    }
+
+    #[test]
+    fn burn_nft_without_edition_refunds_metadata() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let update_authority = Pubkey::new_unique();
+        let mint_authority = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let (nft_data, _) = NFTData::find_address(&program_id, &mint);
+        let (metadata, _) = mpl_token_metadata::pda::find_metadata_account(&mint);
+        let (edition, _) = mpl_token_metadata::pda::find_master_edition_account(&mint);
+        let (redemption, _) = RedemptionData::find_address(&program_id, &mint, &owner);
+        let (freeze_authority, _) = NFTData::find_freeze_authority(&program_id, &mint);
+
+        let nft = NFTData::new_with_details(
+            mint,
+            owner,
+            "Eva Unit-01".to_string(),
+            "EVA".to_string(),
+            "https://example.com/eva.json".to_string(),
+            "Evangelion".to_string(),
+            NFTRarity::Epic,
+            owner,
+            String::new(),
+            None,
+            vec![],
+            0,
+            vec![],
+            0,
+        );
+        let mut token_data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint,
+            owner,
+            amount: 1,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut token_data);
+
+        let mut owner_account = TestAccount::wallet(owner);
+        owner_account.lamports = 1_000_000_000;
+        let mut edition_account = TestAccount::new(edition, system_program::id(), vec![], false);
+        edition_account.lamports = 0;
+        let mut accounts = [
+            owner_account,
+            TestAccount::new(mint, spl_token::id(), vec![0; spl_token::state::Mint::LEN], false),
+            TestAccount::new(Pubkey::new_unique(), spl_token::id(), token_data, false),
+            TestAccount::new(nft_data, program_id, nft.to_packed_vec().unwrap(), false),
+            TestAccount::new(metadata, mpl_token_metadata::id(), vec![0; 679], false),
+            edition_account,
+            TestAccount::new(redemption, system_program::id(), vec![], false),
+            TestAccount::new(system_program::id(), Pubkey::default(), vec![], false),
+            TestAccount::new(spl_token::id(), Pubkey::default(), vec![], false),
+            TestAccount::new(mpl_token_metadata::id(), Pubkey::default(), vec![], false),
+            TestAccount::new(freeze_authority, system_program::id(), vec![], false),
+            TestAccount::wallet(update_authority),
+            TestAccount::wallet(mint_authority),
+        ];
+        let owner_lamports = accounts[0].lamports;
+        let metadata_rent = accounts[4].lamports;
+        let refund = accounts[2].lamports + accounts[3].lamports + metadata_rent;
+        run(&program_id, &mut accounts, |program_id, accounts| {
+            process_burn_nft(program_id, accounts, false)
+        })
+        .unwrap();
+
+        // The master edition created for the burn is refunded along with the metadata
+        assert!(metadata_rent > 0);
+        assert_eq!(accounts[4].lamports, 0);
+        assert_eq!(accounts[5].lamports, 0);
+        assert_eq!(accounts[0].lamports, owner_lamports + refund);
+        assert!(accounts[3].data.is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::program_error::ProgramError;
+
+use crate::error::OtakuVerseError;
+
+/// Length of the header prefixed to every account owned by the program
+///
+/// The header is an 8-byte account type discriminator followed by a one-byte
+/// layout version.
+pub const HEADER_LEN: usize = 9;
+
+/// Split account data into its layout version and body, if it starts with the
+/// header for `discriminator`
+pub fn split_header<'a>(input: &'a [u8], discriminator: &[u8; 8]) -> Option<(u8, &'a [u8])> {
+    if input.len() < HEADER_LEN || input[..8] != discriminator[..] {
+        return None;
+    }
+    Some((input[8], &input[HEADER_LEN..]))
+}
+
+/// An account type stored behind a discriminator and version header
+pub trait VersionedAccount: BorshSerialize + BorshDeserialize {
+    /// Identifies the account type
+    const DISCRIMINATOR: [u8; 8];
+    /// Layout version written by `pack_into_slice`
+    const VERSION: u8;
+
+    /// Deserialize an account, ignoring any trailing bytes
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        match split_header(input, &Self::DISCRIMINATOR) {
+            Some((version, mut body)) if version == Self::VERSION => {
+                Self::deserialize(&mut body).map_err(|_| ProgramError::InvalidAccountData)
+            }
+            Some(_) => Err(OtakuVerseError::UnsupportedAccountVersion.into()),
+            None => Self::unpack_headerless(input),
+        }
+    }
+
+    /// Layout version of a stored record, 0 for records without a header
+    fn stored_version(input: &[u8]) -> u8 {
+        split_header(input, &Self::DISCRIMINATOR).map_or(0, |(version, _)| version)
+    }
+
+    /// Deserialize a record written before the account header existed (version 0)
+    ///
+    /// Account types introduced after the header have no such records.
+    fn unpack_headerless(_input: &[u8]) -> Result<Self, ProgramError> {
+        Err(ProgramError::InvalidAccountData)
+    }
+
+    /// Serialize the header and body at the current version
+    fn to_packed_vec(&self) -> Result<Vec<u8>, ProgramError> {
+        let mut data = Self::DISCRIMINATOR.to_vec();
+        data.push(Self::VERSION);
+        self.serialize(&mut data)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(data)
+    }
+
+    /// Serialize the header and body into an account
+    fn pack_into_slice(&self, output: &mut [u8]) -> Result<(), ProgramError> {
+        let data = self.to_packed_vec()?;
+        output
+            .get_mut(..data.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(&data);
+        Ok(())
+    }
+}
+
+/// Account types that may still hold records from before the account header
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum LegacyAccountType {
+    Config,
+    Community,
+    Message,
+    Reaction,
+    Membership,
+    Ban,
+}
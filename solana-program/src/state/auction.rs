@@ -0,0 +1,142 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::account::{VersionedAccount, HEADER_LEN};
+
+/// Seed prefix for auction PDAs
+pub const AUCTION_SEED: &[u8] = b"auction";
+
+/// An English auction for an NFT held in the mint's escrow
+///
+/// The highest bid is held in the auction account on top of its rent.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct AuctionData {
+    pub mint: Pubkey,
+    pub seller: Pubkey,
+    /// Minimum first bid in lamports
+    pub reserve_price: u64,
+    /// Minimum amount in lamports each bid must add to the highest bid
+    pub min_bid_increment: u64,
+    pub start_at: i64,
+    /// The timestamp bidding closes, pushed back by late bids
+    pub end_at: i64,
+    /// A bid this many seconds before the end extends the auction to this long after the bid
+    pub extension_seconds: i64,
+    pub highest_bidder: Option<Pubkey>,
+    /// The highest bid in lamports (0 before the first bid)
+    pub highest_bid: u64,
+}
+
+impl VersionedAccount for AuctionData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:auc";
+    const VERSION: u8 = 1;
+}
+
+impl AuctionData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 8;
+
+    /// Derive the auction PDA for a mint
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[AUCTION_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Whether bids are accepted at the given timestamp
+    pub fn is_active(&self, now: i64) -> bool {
+        now >= self.start_at && now < self.end_at
+    }
+
+    /// The lowest bid that would currently be accepted
+    pub fn min_next_bid(&self) -> Option<u64> {
+        match self.highest_bidder {
+            Some(_) => self.highest_bid.checked_add(self.min_bid_increment),
+            None => Some(self.reserve_price),
+        }
+    }
+
+    /// Record a new highest bid, extending the auction if it lands near the end
+    pub fn record_bid(&mut self, bidder: Pubkey, amount: u64, now: i64) {
+        self.highest_bidder = Some(bidder);
+        self.highest_bid = amount;
+        if self.end_at - now < self.extension_seconds {
+            self.end_at = now.saturating_add(self.extension_seconds);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auction() -> AuctionData {
+        AuctionData {
+            mint: Pubkey::new_unique(),
+            seller: Pubkey::new_unique(),
+            reserve_price: 1_000,
+            min_bid_increment: 100,
+            start_at: 1_000,
+            end_at: 2_000,
+            extension_seconds: 300,
+            highest_bidder: None,
+            highest_bid: 0,
+        }
+    }
+
+    #[test]
+    fn is_active_between_start_and_end() {
+        let auction = auction();
+        assert!(!auction.is_active(999));
+        assert!(auction.is_active(1_000));
+        assert!(auction.is_active(1_999));
+        assert!(!auction.is_active(2_000));
+    }
+
+    #[test]
+    fn min_next_bid_starts_at_reserve_then_adds_increment() {
+        let mut auction = auction();
+        assert_eq!(auction.min_next_bid(), Some(1_000));
+
+        auction.record_bid(Pubkey::new_unique(), 1_000, 1_100);
+        assert_eq!(auction.min_next_bid(), Some(1_100));
+
+        auction.record_bid(Pubkey::new_unique(), 5_000, 1_200);
+        assert_eq!(auction.min_next_bid(), Some(5_100));
+
+        auction.highest_bid = u64::MAX;
+        assert_eq!(auction.min_next_bid(), None);
+    }
+
+    #[test]
+    fn early_bid_keeps_end() {
+        let mut auction = auction();
+        let bidder = Pubkey::new_unique();
+        auction.record_bid(bidder, 1_000, 1_100);
+
+        assert_eq!(auction.highest_bidder, Some(bidder));
+        assert_eq!(auction.highest_bid, 1_000);
+        assert_eq!(auction.end_at, 2_000);
+
+        // A bid exactly `extension_seconds` before the end is not late
+        auction.record_bid(bidder, 1_100, 1_700);
+        assert_eq!(auction.end_at, 2_000);
+    }
+
+    #[test]
+    fn late_bid_extends_end() {
+        let mut auction = auction();
+        auction.record_bid(Pubkey::new_unique(), 1_000, 1_900);
+        assert_eq!(auction.end_at, 2_200);
+
+        // Each late bid pushes the end back again
+        auction.record_bid(Pubkey::new_unique(), 1_100, 2_150);
+        assert_eq!(auction.end_at, 2_450);
+    }
+
+    #[test]
+    fn no_extension_without_window() {
+        let mut auction = auction();
+        auction.extension_seconds = 0;
+        auction.record_bid(Pubkey::new_unique(), 1_000, 1_999);
+        assert_eq!(auction.end_at, 2_000);
+    }
+}
+
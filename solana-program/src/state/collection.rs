@@ -0,0 +1,55 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    error::OtakuVerseError,
+    state::{
+        account::{VersionedAccount, HEADER_LEN},
+        nft::MAX_NAME_LEN,
+    },
+};
+
+/// Seed prefix for collection PDAs
+pub const COLLECTION_SEED: &[u8] = b"collection";
+
+/// An OtakuVerse collection, backed by a Metaplex collection NFT
+///
+/// The collection PDA is the update authority of the collection NFT's metadata,
+/// so the program can verify items into the collection.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct CollectionData {
+    /// The mint of the collection NFT
+    pub mint: Pubkey,
+    /// The account allowed to mint into the collection
+    pub authority: Pubkey,
+    pub name: String,
+    /// Number of NFTs verified into the collection
+    pub size: u64,
+    pub created_at: i64,
+}
+
+impl VersionedAccount for CollectionData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:col";
+    const VERSION: u8 = 1;
+}
+
+impl CollectionData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 4 + MAX_NAME_LEN + 8 + 8;
+
+    /// Derive the collection PDA for a collection mint
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[COLLECTION_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Count an NFT verified into the collection
+    pub fn add_item(&mut self) -> Result<(), OtakuVerseError> {
+        self.size = self.size.checked_add(1).ok_or(OtakuVerseError::MathOverflow)?;
+        Ok(())
+    }
+
+    /// Stop counting a burned NFT
+    pub fn remove_item(&mut self) -> Result<(), OtakuVerseError> {
+        self.size = self.size.checked_sub(1).ok_or(OtakuVerseError::MathOverflow)?;
+        Ok(())
+    }
+}
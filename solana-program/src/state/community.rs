@@ -1,28 +1,315 @@
 use borsh::{BorshSerialize, BorshDeserialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::state::account::{VersionedAccount, HEADER_LEN};
+
+/// Seed prefix for community PDAs
+pub const COMMUNITY_SEED: &[u8] = b"community";
+
+/// Seed prefix for message PDAs
+pub const MESSAGE_SEED: &[u8] = b"message";
+
+/// Seed prefix for membership PDAs
+pub const MEMBERSHIP_SEED: &[u8] = b"membership";
+
+/// Seed prefix for ban record PDAs
+pub const BAN_SEED: &[u8] = b"ban";
+
+/// Seed prefix for reaction PDAs
+pub const REACTION_SEED: &[u8] = b"reaction";
+
+/// Maximum length of a community ID (it is used as a PDA seed)
+pub const MAX_COMMUNITY_ID_LEN: usize = 32;
+/// Maximum length of a community name
+pub const MAX_COMMUNITY_NAME_LEN: usize = 64;
+/// Maximum length of a community description
+pub const MAX_COMMUNITY_DESCRIPTION_LEN: usize = 256;
+/// Maximum length of a message's content
+pub const MAX_MESSAGE_LEN: usize = 500;
+/// Maximum number of moderators per community
+pub const MAX_MODERATORS: usize = 8;
+/// Maximum number of distinct emoji tallied on a message
+pub const MAX_REACTION_KINDS: usize = 16;
 
 /// Community data structure
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
-#[derive(BorshSerialize, BorshDeserialize)]
 pub struct CommunityData {
     pub id: String,
     pub name: String,
     pub description: String,
     pub creator: Pubkey,
     pub created_at: i64,
+    /// Number of messages sent, and the index of the next message
+    pub message_count: u64,
+    /// Number of current members
+    pub member_count: u64,
+    /// Members appointed by the creator to moderate the community
+    pub moderators: Vec<Pubkey>,
+}
+
+impl VersionedAccount for CommunityData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:com";
+    const VERSION: u8 = 1;
+
+    /// Communities from before the account header hold the version 1 body
+    fn unpack_headerless(mut input: &[u8]) -> Result<Self, ProgramError> {
+        Self::deserialize(&mut input).map_err(|_| ProgramError::InvalidAccountData)
+    }
 }
 
 impl CommunityData {
-    pub fn unpack_from_slice(input: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        Self::try_from_slice(input).map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)
+    /// Account size with every string at its maximum length
+    pub const LEN: usize = HEADER_LEN
+        + 4 + MAX_COMMUNITY_ID_LEN
+        + 4 + MAX_COMMUNITY_NAME_LEN
+        + 4 + MAX_COMMUNITY_DESCRIPTION_LEN
+        + 32
+        + 8
+        + 8
+        + 8
+        + 4 + 32 * MAX_MODERATORS;
+
+    /// Derive the community PDA for a community ID
+    pub fn find_address(program_id: &Pubkey, id: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[COMMUNITY_SEED, id.as_bytes()], program_id)
+    }
+
+    /// Whether the key may moderate the community (the creator always can)
+    pub fn can_moderate(&self, key: &Pubkey) -> bool {
+        self.creator == *key || self.moderators.contains(key)
+    }
+
+    /// Add a moderator, returning false if it is already present or the list is full
+    pub fn add_moderator(&mut self, moderator: Pubkey) -> bool {
+        if self.moderators.contains(&moderator) || self.moderators.len() >= MAX_MODERATORS {
+            return false;
+        }
+        self.moderators.push(moderator);
+        true
     }
+
+    /// Remove a moderator, returning false if it was not present
+    pub fn remove_moderator(&mut self, moderator: &Pubkey) -> bool {
+        let len = self.moderators.len();
+        self.moderators.retain(|key| key != moderator);
+        self.moderators.len() != len
+    }
+
+    /// Whether the name and description fit in the account
+    pub fn is_valid_details(name: &str, description: &str) -> bool {
+        name.len() <= MAX_COMMUNITY_NAME_LEN && description.len() <= MAX_COMMUNITY_DESCRIPTION_LEN
+    }
+}
+
+/// Record left in a closed community's account so its ID cannot be reused
+///
+/// Messages, memberships, bans and reactions are derived from the community account, so a
+/// new community at the same address would inherit them.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ClosedCommunityData {
+    pub id: String,
+    pub closed_at: i64,
+}
+
+impl VersionedAccount for ClosedCommunityData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:ccm";
+    const VERSION: u8 = 1;
+}
+
+impl ClosedCommunityData {
+    pub const LEN: usize = HEADER_LEN + 4 + MAX_COMMUNITY_ID_LEN + 8;
 }
 
 /// Message data structure
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub struct MessageData {
     pub community_id: String,
+    /// Position of the message in its community, starting at 0
+    pub index: u64,
     pub sender: Pubkey,
     pub content: String,
     pub sent_at: i64,
+    /// Whether a moderator has hidden the message
+    pub is_hidden: bool,
+    /// Index of the message this replies to (if any)
+    pub reply_to: Option<u64>,
+    /// Index of the first message of the thread this belongs to (if a reply)
+    pub thread_root: Option<u64>,
+    /// Number of direct replies
+    pub reply_count: u64,
+    /// Reaction tallies by emoji
+    pub reactions: Vec<ReactionCount>,
+}
+
+/// Number of reactions with a given emoji on a message
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReactionCount {
+    /// Unicode code point of the emoji
+    pub emoji_code: u32,
+    pub count: u64,
+}
+
+impl VersionedAccount for MessageData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:msg";
+    const VERSION: u8 = 1;
+
+    /// Messages from before the account header hold the version 1 body
+    fn unpack_headerless(mut input: &[u8]) -> Result<Self, ProgramError> {
+        Self::deserialize(&mut input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl MessageData {
+    /// Derive the message PDA for a community account and message index
+    pub fn find_address(program_id: &Pubkey, community: &Pubkey, index: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[MESSAGE_SEED, community.as_ref(), &index.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Count a reaction, returning false if the message has no room for a new emoji
+    pub fn add_reaction(&mut self, emoji_code: u32) -> bool {
+        if let Some(reaction) = self.reactions.iter_mut().find(|r| r.emoji_code == emoji_code) {
+            reaction.count += 1;
+            return true;
+        }
+        if self.reactions.len() >= MAX_REACTION_KINDS {
+            return false;
+        }
+        self.reactions.push(ReactionCount { emoji_code, count: 1 });
+        true
+    }
+
+    /// Uncount a reaction, dropping the emoji once nobody uses it
+    pub fn remove_reaction(&mut self, emoji_code: u32) {
+        if let Some(reaction) = self.reactions.iter_mut().find(|r| r.emoji_code == emoji_code) {
+            reaction.count = reaction.count.saturating_sub(1);
+        }
+        self.reactions.retain(|r| r.count > 0);
+    }
 }
+
+/// A user's reaction to a message
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReactionData {
+    pub message: Pubkey,
+    pub user: Pubkey,
+    pub emoji_code: u32,
+    pub reacted_at: i64,
+}
+
+impl VersionedAccount for ReactionData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:rct";
+    const VERSION: u8 = 1;
+
+    /// Reactions from before the account header hold the version 1 body
+    fn unpack_headerless(mut input: &[u8]) -> Result<Self, ProgramError> {
+        Self::deserialize(&mut input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl ReactionData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 4 + 8;
+
+    /// Derive the reaction PDA for a message account, user and emoji
+    pub fn find_address(
+        program_id: &Pubkey,
+        message: &Pubkey,
+        user: &Pubkey,
+        emoji_code: u32,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[REACTION_SEED, message.as_ref(), user.as_ref(), &emoji_code.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+/// Membership of a user in a community
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct MembershipData {
+    pub community: Pubkey,
+    pub member: Pubkey,
+    pub joined_at: i64,
+}
+
+impl VersionedAccount for MembershipData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:mbr";
+    const VERSION: u8 = 1;
+
+    /// Memberships from before the account header hold the version 1 body
+    fn unpack_headerless(mut input: &[u8]) -> Result<Self, ProgramError> {
+        Self::deserialize(&mut input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl MembershipData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 8;
+
+    /// Derive the membership PDA for a community account and user
+    pub fn find_address(program_id: &Pubkey, community: &Pubkey, member: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[MEMBERSHIP_SEED, community.as_ref(), member.as_ref()],
+            program_id,
+        )
+    }
+}
+
+/// Record of a user banned from a community
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct BanData {
+    pub community: Pubkey,
+    pub user: Pubkey,
+    pub banned_by: Pubkey,
+    pub banned_at: i64,
+}
+
+impl VersionedAccount for BanData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:ban";
+    const VERSION: u8 = 1;
+
+    /// Ban records from before the account header hold the version 1 body
+    fn unpack_headerless(mut input: &[u8]) -> Result<Self, ProgramError> {
+        Self::deserialize(&mut input).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl BanData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 32 + 8;
+
+    /// Derive the ban record PDA for a community account and user
+    pub fn find_address(program_id: &Pubkey, community: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[BAN_SEED, community.as_ref(), user.as_ref()], program_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_records_without_header() {
+        let membership = MembershipData {
+            community: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+            joined_at: 1_700_000_000,
+        };
+
+        let headerless = membership.try_to_vec().unwrap();
+        assert_eq!(MembershipData::stored_version(&headerless), 0);
+        assert_eq!(MembershipData::unpack_from_slice(&headerless).unwrap(), membership);
+
+        let stored = membership.to_packed_vec().unwrap();
+        assert_eq!(MembershipData::stored_version(&stored), MembershipData::VERSION);
+        assert_eq!(MembershipData::unpack_from_slice(&stored).unwrap(), membership);
+    }
+
+    #[test]
+    fn tombstone_does_not_unpack_as_community() {
+        let closed = ClosedCommunityData { id: "frieren".to_string(), closed_at: 1_700_000_000 };
+        let stored = closed.to_packed_vec().unwrap();
+        assert!(CommunityData::unpack_from_slice(&stored).is_err());
+    }
+}
+
@@ -0,0 +1,160 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    error::OtakuVerseError,
+    state::account::{split_header, VersionedAccount, HEADER_LEN},
+};
+
+/// Seed for the singleton program config PDA
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Maximum number of platform admins in the registry
+pub const MAX_ADMINS: usize = 16;
+
+/// Maximum number of SPL token mints accepted as marketplace payment
+pub const MAX_PAYMENT_MINTS: usize = 8;
+
+/// Program-wide configuration
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ProgramConfig {
+    /// The super admin allowed to change the config
+    pub authority: Pubkey,
+    /// The account receiving platform fees
+    pub treasury: Pubkey,
+    /// The platform fee on marketplace sales (in basis points, e.g. 250 = 2.5%)
+    pub platform_fee_basis_points: u16,
+    /// Platform admins allowed to verify NFTs
+    pub admins: Vec<Pubkey>,
+    /// SPL token mints listings may be priced in, besides SOL
+    pub payment_mints: Vec<Pubkey>,
+}
+
+impl VersionedAccount for ProgramConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:cfg";
+    const VERSION: u8 = 2;
+
+    /// Deserialize the config at any known layout version
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let config = match split_header(input, &Self::DISCRIMINATOR) {
+            Some((Self::VERSION, mut body)) => Self::deserialize(&mut body),
+            Some((1, mut body)) => ProgramConfigV1::deserialize(&mut body).map(Self::from),
+            Some(_) => return Err(OtakuVerseError::UnsupportedAccountVersion.into()),
+            None => ProgramConfigV1::deserialize(&mut &input[..]).map(Self::from),
+        };
+        config.map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+impl ProgramConfig {
+    /// Account size with a full admin registry and payment mint allowlist
+    pub const LEN: usize = HEADER_LEN
+        + 32
+        + 32
+        + 2
+        + 4 + 32 * MAX_ADMINS
+        + 4 + 32 * MAX_PAYMENT_MINTS;
+
+    /// Derive the program config PDA
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+    }
+
+    /// Calculate the platform fee for a given sale price
+    pub fn calculate_platform_fee(&self, sale_price: u64) -> u64 {
+        (sale_price as u128 * self.platform_fee_basis_points as u128 / 10000) as u64
+    }
+
+    /// Whether the key is a platform admin
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admins.contains(key)
+    }
+
+    /// Add a platform admin, returning false if it is already present or the registry is full
+    pub fn add_admin(&mut self, admin: Pubkey) -> bool {
+        if self.is_admin(&admin) || self.admins.len() >= MAX_ADMINS {
+            return false;
+        }
+        self.admins.push(admin);
+        true
+    }
+
+    /// Remove a platform admin, returning false if it was not present
+    pub fn remove_admin(&mut self, admin: &Pubkey) -> bool {
+        let len = self.admins.len();
+        self.admins.retain(|key| key != admin);
+        self.admins.len() != len
+    }
+
+    /// Whether listings may be priced in the mint
+    pub fn accepts_payment_mint(&self, mint: &Pubkey) -> bool {
+        self.payment_mints.contains(mint)
+    }
+
+    /// Allow a payment mint, returning false if it is already allowed or the allowlist is full
+    pub fn add_payment_mint(&mut self, mint: Pubkey) -> bool {
+        if self.accepts_payment_mint(&mint) || self.payment_mints.len() >= MAX_PAYMENT_MINTS {
+            return false;
+        }
+        self.payment_mints.push(mint);
+        true
+    }
+
+    /// Disallow a payment mint, returning false if it was not allowed
+    pub fn remove_payment_mint(&mut self, mint: &Pubkey) -> bool {
+        let len = self.payment_mints.len();
+        self.payment_mints.retain(|key| key != mint);
+        self.payment_mints.len() != len
+    }
+}
+
+/// Layout of the config version 1, also the body of configs written before the
+/// account header (version 0)
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+struct ProgramConfigV1 {
+    authority: Pubkey,
+    treasury: Pubkey,
+    platform_fee_basis_points: u16,
+    admins: Vec<Pubkey>,
+}
+
+impl From<ProgramConfigV1> for ProgramConfig {
+    /// Configs from before SPL token payments accept only SOL
+    fn from(config: ProgramConfigV1) -> Self {
+        Self {
+            authority: config.authority,
+            treasury: config.treasury,
+            platform_fee_basis_points: config.platform_fee_basis_points,
+            admins: config.admins,
+            payment_mints: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpacks_every_layout_version() {
+        let v1 = ProgramConfigV1 {
+            authority: Pubkey::new_unique(),
+            treasury: Pubkey::new_unique(),
+            platform_fee_basis_points: 250,
+            admins: vec![Pubkey::new_unique()],
+        };
+        let config = ProgramConfig::from(v1.clone());
+
+        let headerless = v1.try_to_vec().unwrap();
+        assert_eq!(ProgramConfig::unpack_from_slice(&headerless).unwrap(), config);
+
+        let mut stored = ProgramConfig::DISCRIMINATOR.to_vec();
+        stored.push(1);
+        v1.serialize(&mut stored).unwrap();
+        assert_eq!(ProgramConfig::unpack_from_slice(&stored).unwrap(), config);
+
+        let stored = config.to_packed_vec().unwrap();
+        assert_eq!(ProgramConfig::unpack_from_slice(&stored).unwrap(), config);
+    }
+}
+
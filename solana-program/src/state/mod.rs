@@ -1,2 +1,9 @@
+pub mod account;
 pub mod nft;
 pub mod community;
+pub mod config;
+pub mod supply;
+pub mod collection;
+pub mod offer;
+pub mod auction;
+pub mod redemption;
@@ -1,5 +1,41 @@
+use std::io;
+use std::str::FromStr;
+
 use borsh::{BorshSerialize, BorshDeserialize};
-use solana_program::pubkey::Pubkey;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    error::OtakuVerseError,
+    state::account::{split_header, VersionedAccount, HEADER_LEN},
+};
+
+/// Seed prefix for the per-mint NFT data PDA
+pub const NFT_DATA_SEED: &[u8] = b"nft_data";
+
+/// Seed prefix for the per-mint escrow authority PDA that holds listed NFTs
+pub const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Seed prefix for the per-mint PDA that freezes soulbound NFTs
+pub const SOULBOUND_SEED: &[u8] = b"soulbound";
+
+/// Maximum length of an NFT name (the Metaplex limit)
+pub const MAX_NAME_LEN: usize = 32;
+/// Maximum length of an NFT symbol (the Metaplex limit)
+pub const MAX_SYMBOL_LEN: usize = 10;
+/// Maximum length of an NFT metadata URI (the Metaplex limit)
+pub const MAX_URI_LEN: usize = 200;
+/// Maximum length of the anime an NFT belongs to
+pub const MAX_ANIME_TITLE_LEN: usize = 64;
+/// Maximum length of an NFT description
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+/// Maximum number of attributes per NFT
+pub const MAX_ATTRIBUTES: usize = 16;
+/// Maximum length of an attribute's trait type
+pub const MAX_TRAIT_TYPE_LEN: usize = 32;
+/// Maximum length of an attribute's value
+pub const MAX_ATTRIBUTE_VALUE_LEN: usize = 64;
+/// Maximum number of creators sharing an NFT's royalties (the Metaplex limit)
+pub const MAX_CREATORS: usize = 5;
 
 /// NFT rarity enum
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
@@ -10,14 +46,32 @@ pub enum NFTRarity {
     Legendary,
 }
 
-impl NFTRarity {
-    pub fn from_str(s: &str) -> Self {
+impl FromStr for NFTRarity {
+    type Err = OtakuVerseError;
+
+    /// Parse a rarity name, case-insensitively
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "common" => NFTRarity::Common,
-            "rare" => NFTRarity::Rare,
-            "epic" => NFTRarity::Epic,
-            "legendary" => NFTRarity::Legendary,
-            _ => NFTRarity::Common,
+            "common" => Ok(NFTRarity::Common),
+            "rare" => Ok(NFTRarity::Rare),
+            "epic" => Ok(NFTRarity::Epic),
+            "legendary" => Ok(NFTRarity::Legendary),
+            _ => Err(OtakuVerseError::InvalidRarity),
+        }
+    }
+}
+
+impl NFTRarity {
+    /// Number of rarity tiers
+    pub const COUNT: usize = 4;
+
+    /// Position of the tier, from 0 for Common to 3 for Legendary
+    pub fn index(&self) -> usize {
+        match self {
+            NFTRarity::Common => 0,
+            NFTRarity::Rare => 1,
+            NFTRarity::Epic => 2,
+            NFTRarity::Legendary => 3,
         }
     }
 
@@ -31,16 +85,187 @@ impl NFTRarity {
     }
 }
 
+/// How wallets display an attribute's value, per the Metaplex JSON standard
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DisplayType {
+    /// `"number"`: the value is a number
+    Number,
+    /// `"boost_number"`: the value is a number shown as a boost
+    BoostNumber,
+    /// `"boost_percentage"`: the value is a number shown as a percentage boost
+    BoostPercentage,
+    /// `"date"`: the value is a unix timestamp
+    Date,
+}
+
+impl DisplayType {
+    /// The `display_type` string used in the metadata JSON
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisplayType::Number => "number",
+            DisplayType::BoostNumber => "boost_number",
+            DisplayType::BoostPercentage => "boost_percentage",
+            DisplayType::Date => "date",
+        }
+    }
+
+    /// Whether the value can be displayed this way
+    pub fn accepts(&self, value: &str) -> bool {
+        match self {
+            DisplayType::Number | DisplayType::BoostNumber | DisplayType::BoostPercentage => {
+                value.parse::<f64>().map_or(false, f64::is_finite)
+            }
+            DisplayType::Date => value.parse::<i64>().is_ok(),
+        }
+    }
+}
+
 /// NFT attribute structure
+///
+/// An NFT has at most one attribute per `trait_type`.
 #[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
 pub struct NFTAttribute {
     pub trait_type: String,
     pub value: String,
+    /// How the value is displayed (None for plain text)
+    pub display_type: Option<DisplayType>,
+}
+
+impl NFTAttribute {
+    /// Serialized size with both strings at their maximum length
+    pub const LEN: usize = 4 + MAX_TRAIT_TYPE_LEN + 4 + MAX_ATTRIBUTE_VALUE_LEN + 1 + 1;
+
+    /// Check the attribute fits within its limits and its value suits its display type
+    pub fn validate(&self) -> Result<(), OtakuVerseError> {
+        if self.trait_type.len() > MAX_TRAIT_TYPE_LEN || self.value.len() > MAX_ATTRIBUTE_VALUE_LEN {
+            return Err(OtakuVerseError::AttributeTooLong);
+        }
+        if let Some(display_type) = self.display_type {
+            if !display_type.accepts(&self.value) {
+                return Err(OtakuVerseError::InvalidAttributeValue);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Attribute layout of NFT data version 1 and earlier, also accepted by the
+/// original mint and update instructions
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct NFTAttributeV1 {
+    pub trait_type: String,
+    pub value: String,
+}
+
+impl From<NFTAttributeV1> for NFTAttribute {
+    fn from(attribute: NFTAttributeV1) -> Self {
+        Self {
+            trait_type: attribute.trait_type,
+            value: attribute.value,
+            display_type: None,
+        }
+    }
+}
+
+/// A creator entitled to a share of an NFT's royalties
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub struct NFTCreator {
+    pub address: Pubkey,
+    /// Percentage of the royalties paid to this creator
+    pub share: u8,
+}
+
+impl NFTCreator {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// How a listed NFT is priced
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq)]
+pub enum ListingType {
+    /// Sold at the listing price
+    #[default]
+    FixedPrice,
+    /// Dutch auction: the price falls from the listing price to `floor_price`
+    /// between `start_at` and `end_at`
+    DecliningPrice {
+        floor_price: u64,
+        start_at: i64,
+        end_at: i64,
+        decay: PriceDecay,
+    },
+}
+
+/// How a declining price falls over its window
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum PriceDecay {
+    /// Falls continuously
+    Linear,
+    /// Falls in equal drops every `step_seconds`
+    Stepwise { step_seconds: i64 },
+}
+
+impl ListingType {
+    pub const LEN: usize = 1 + 8 + 8 + 8 + 1 + 8;
+
+    /// Check a declining price falls to a lower floor over a non-empty window
+    pub fn validate(&self, start_price: u64) -> Result<(), OtakuVerseError> {
+        if let Self::DecliningPrice { floor_price, start_at, end_at, decay } = self {
+            if *floor_price == 0 || *floor_price >= start_price || end_at <= start_at {
+                return Err(OtakuVerseError::InvalidPriceSchedule);
+            }
+            if let PriceDecay::Stepwise { step_seconds } = decay {
+                if *step_seconds <= 0 {
+                    return Err(OtakuVerseError::InvalidPriceSchedule);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The price at a timestamp for a listing starting at `start_price`
+    pub fn price_at(&self, start_price: u64, now: i64) -> u64 {
+        let (floor_price, start_at, end_at, decay) = match self {
+            Self::FixedPrice => return start_price,
+            Self::DecliningPrice { floor_price, start_at, end_at, decay } => {
+                (*floor_price, *start_at, *end_at, decay)
+            }
+        };
+        if now <= start_at {
+            return start_price;
+        }
+        if now >= end_at {
+            return floor_price;
+        }
+
+        // Widen so extreme timestamps cannot overflow; the drop stays below the price range
+        let mut elapsed = now as i128 - start_at as i128;
+        if let PriceDecay::Stepwise { step_seconds } = decay {
+            elapsed -= elapsed % *step_seconds as i128;
+        }
+        let window = end_at as i128 - start_at as i128;
+        let drop = (start_price - floor_price) as u128 * elapsed as u128 / window as u128;
+        start_price - drop as u64
+    }
+}
+
+/// An NFT's place in a Metaplex edition run
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq)]
+pub enum NFTEdition {
+    /// The design prints are made from
+    Master {
+        /// Maximum number of prints (None if unlimited)
+        max_supply: Option<u64>,
+    },
+    /// A numbered print of a master edition
+    Print { master_mint: Pubkey, number: u64 },
+}
+
+impl NFTEdition {
+    pub const LEN: usize = 1 + 32 + 8;
 }
 
 /// NFT data structure
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
-#[derive(BorshSerialize, BorshDeserialize)]
 pub struct NFTData {
     pub mint: Pubkey,
     pub owner: Pubkey,
@@ -66,13 +291,197 @@ pub struct NFTData {
     pub royalty_basis_points: u16,
     /// Whether the NFT has been verified by the OtakuVerse platform
     pub is_verified: bool,
+    /// The creators sharing the royalties (shares sum to 100, or empty for none)
+    pub creators: Vec<NFTCreator>,
+    /// How the listing is priced (if for sale), starting from `price`
+    pub listing_type: ListingType,
+    /// The SPL token mint the listing is priced in, in its base units (None for lamports)
+    pub payment_mint: Option<Pubkey>,
+    /// The master edition or print this NFT is (None for a one-off)
+    pub edition: Option<NFTEdition>,
+    /// Whether the holder's token account is frozen so the NFT cannot be transferred
+    pub is_soulbound: bool,
+}
+
+impl VersionedAccount for NFTData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:nft";
+    const VERSION: u8 = 7;
+
+    /// Deserialize NFT data at any known layout version
+    ///
+    /// Version 0 records predate the account header and hold a version 1 body.
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let (version, mut body) = match split_header(input, &Self::DISCRIMINATOR) {
+            Some((version, body)) if version <= Self::VERSION => (version, body),
+            Some(_) => return Err(OtakuVerseError::UnsupportedAccountVersion.into()),
+            None => (0, input),
+        };
+        Self::deserialize_version(&mut body, version)
+            .map_err(|_| ProgramError::InvalidAccountData)
+    }
 }
 
 impl NFTData {
-    /// Set the NFT for sale
-    pub fn set_for_sale(&mut self, price: u64) {
+    /// Account size with every string and list at its maximum length
+    pub const LEN: usize = HEADER_LEN
+        + 32
+        + 32
+        + 4 + MAX_NAME_LEN
+        + 4 + MAX_SYMBOL_LEN
+        + 4 + MAX_URI_LEN
+        + 4 + MAX_ANIME_TITLE_LEN
+        + 1
+        + 1
+        + 8
+        + 8
+        + 32
+        + 4 + MAX_DESCRIPTION_LEN
+        + 1 + 32
+        + 4 + NFTAttribute::LEN * MAX_ATTRIBUTES
+        + 2
+        + 1
+        + 4 + NFTCreator::LEN * MAX_CREATORS
+        + ListingType::LEN
+        + 1 + 32
+        + 1 + NFTEdition::LEN
+        + 1;
+
+    /// Check every string and the attribute list fit within their limits
+    pub fn validate(&self) -> Result<(), OtakuVerseError> {
+        if self.name.len() > MAX_NAME_LEN {
+            return Err(OtakuVerseError::NameTooLong);
+        }
+        if self.symbol.len() > MAX_SYMBOL_LEN {
+            return Err(OtakuVerseError::SymbolTooLong);
+        }
+        if self.uri.len() > MAX_URI_LEN {
+            return Err(OtakuVerseError::UriTooLong);
+        }
+        if self.anime_title.len() > MAX_ANIME_TITLE_LEN {
+            return Err(OtakuVerseError::AnimeTitleTooLong);
+        }
+        if self.description.len() > MAX_DESCRIPTION_LEN {
+            return Err(OtakuVerseError::DescriptionTooLong);
+        }
+        if self.attributes.len() > MAX_ATTRIBUTES {
+            return Err(OtakuVerseError::TooManyAttributes);
+        }
+        if self.royalty_basis_points > 10000 {
+            return Err(OtakuVerseError::InvalidBasisPoints);
+        }
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            attribute.validate()?;
+            if self.attributes[..i].iter().any(|a| a.trait_type == attribute.trait_type) {
+                return Err(OtakuVerseError::DuplicateAttribute);
+            }
+        }
+        Self::validate_creators(&self.creators)
+    }
+
+    /// Check there are at most `MAX_CREATORS` distinct creators whose shares sum to 100
+    pub fn validate_creators(creators: &[NFTCreator]) -> Result<(), OtakuVerseError> {
+        if creators.len() > MAX_CREATORS {
+            return Err(OtakuVerseError::TooManyCreators);
+        }
+        for (i, creator) in creators.iter().enumerate() {
+            if creators[..i].iter().any(|c| c.address == creator.address) {
+                return Err(OtakuVerseError::DuplicateCreator);
+            }
+        }
+        let total_share: u32 = creators.iter().map(|c| c.share as u32).sum();
+        if !creators.is_empty() && total_share != 100 {
+            return Err(OtakuVerseError::InvalidCreatorShares);
+        }
+        Ok(())
+    }
+
+    /// Decode a record body stored at a layout version
+    ///
+    /// Each version after 2 appended fields to the previous layout, and fields a
+    /// record predates take the value they had before the field existed.
+    fn deserialize_version(body: &mut &[u8], version: u8) -> io::Result<Self> {
+        let head = NFTDataHead::deserialize(body)?;
+        // Attributes had no display types before version 2
+        let attributes = if version >= 2 {
+            Vec::<NFTAttribute>::deserialize(body)?
+        } else {
+            Vec::<NFTAttributeV1>::deserialize(body)?
+                .into_iter()
+                .map(NFTAttribute::from)
+                .collect()
+        };
+        let (royalty_basis_points, is_verified) = <(u16, bool)>::deserialize(body)?;
+        // Royalties from before multi-creator splits all go to the single creator
+        let creators = if version >= 3 {
+            Vec::<NFTCreator>::deserialize(body)?
+        } else {
+            vec![NFTCreator { address: head.creator, share: 100 }]
+        };
+        let listing_type = if version >= 4 {
+            ListingType::deserialize(body)?
+        } else {
+            ListingType::FixedPrice
+        };
+        let payment_mint = if version >= 5 { Option::deserialize(body)? } else { None };
+        let edition = if version >= 6 { Option::deserialize(body)? } else { None };
+        let is_soulbound = if version >= 7 { bool::deserialize(body)? } else { false };
+
+        Ok(Self {
+            mint: head.mint,
+            owner: head.owner,
+            name: head.name,
+            symbol: head.symbol,
+            uri: head.uri,
+            anime_title: head.anime_title,
+            rarity: head.rarity,
+            is_for_sale: head.is_for_sale,
+            price: head.price,
+            created_at: head.created_at,
+            creator: head.creator,
+            description: head.description,
+            collection_id: head.collection_id,
+            attributes,
+            royalty_basis_points,
+            is_verified,
+            creators,
+            listing_type,
+            payment_mint,
+            edition,
+            is_soulbound,
+        })
+    }
+
+    /// Derive the NFT data PDA for a mint
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NFT_DATA_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Derive the escrow authority PDA for a mint
+    pub fn find_escrow_authority(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ESCROW_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Derive the freeze authority PDA of a soulbound mint
+    pub fn find_freeze_authority(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SOULBOUND_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Set the NFT for sale, starting at `price` in lamports or `payment_mint` tokens
+    pub fn set_for_sale(
+        &mut self,
+        price: u64,
+        listing_type: ListingType,
+        payment_mint: Option<Pubkey>,
+    ) {
         self.is_for_sale = true;
         self.price = price;
+        self.listing_type = listing_type;
+        self.payment_mint = payment_mint;
+    }
+
+    /// The price a buyer pays at a timestamp
+    pub fn current_price(&self, now: i64) -> u64 {
+        self.listing_type.price_at(self.price, now)
     }
 
     /// Remove the NFT from sale
@@ -91,9 +500,28 @@ impl NFTData {
         self.is_verified = true;
     }
 
-    /// Add an attribute to the NFT
-    pub fn add_attribute(&mut self, trait_type: String, value: String) {
-        self.attributes.push(NFTAttribute { trait_type, value });
+    /// Revoke the NFT's verification
+    pub fn unverify(&mut self) {
+        self.is_verified = false;
+    }
+
+    /// Add an attribute to the NFT, replacing any with the same trait type
+    pub fn set_attribute(&mut self, attribute: NFTAttribute) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|a| a.trait_type == attribute.trait_type)
+        {
+            Some(existing) => *existing = attribute,
+            None => self.attributes.push(attribute),
+        }
+    }
+
+    /// Remove the attribute with a trait type, returning false if there was none
+    pub fn remove_attribute(&mut self, trait_type: &str) -> bool {
+        let len = self.attributes.len();
+        self.attributes.retain(|a| a.trait_type != trait_type);
+        self.attributes.len() != len
     }
 
     /// Set the collection ID for the NFT
@@ -118,6 +546,17 @@ impl NFTData {
     pub fn calculate_royalty(&self, sale_price: u64) -> u64 {
         (sale_price as u128 * self.royalty_basis_points as u128 / 10000) as u64
     }
+
+    /// Split the royalty for a sale price between the creators by share
+    ///
+    /// Rounding dust is not assigned to any creator and stays with the seller.
+    pub fn royalty_split(&self, sale_price: u64) -> Vec<(Pubkey, u64)> {
+        let royalty = self.calculate_royalty(sale_price);
+        self.creators
+            .iter()
+            .map(|c| (c.address, (royalty as u128 * c.share as u128 / 100) as u64))
+            .collect()
+    }
 }
 impl NFTData {
     pub fn new() -> Self {
@@ -138,6 +577,11 @@ impl NFTData {
             attributes: vec![],
             royalty_basis_points: 0,
             is_verified: false,
+            creators: vec![],
+            listing_type: ListingType::FixedPrice,
+            payment_mint: None,
+            edition: None,
+            is_soulbound: false,
         }
     }
     pub fn new_with_details(
@@ -153,6 +597,8 @@ impl NFTData {
         collection_id: Option<Pubkey>,
         attributes: Vec<NFTAttribute>,
         royalty_basis_points: u16,
+        creators: Vec<NFTCreator>,
+        created_at: i64,
     ) -> Self {
         Self {
             mint,
@@ -164,13 +610,336 @@ impl NFTData {
             rarity,
             is_for_sale: false,
             price: 0,
-            created_at: 0,
+            created_at,
             creator,
             description,
             collection_id,
             attributes,
             royalty_basis_points,
             is_verified: false,
+            creators,
+            listing_type: ListingType::FixedPrice,
+            payment_mint: None,
+            edition: None,
+            is_soulbound: false,
+        }
+    }
+}
+
+/// Fields shared by every NFT data layout, up to the attributes
+#[derive(BorshDeserialize)]
+struct NFTDataHead {
+    mint: Pubkey,
+    owner: Pubkey,
+    name: String,
+    symbol: String,
+    uri: String,
+    anime_title: String,
+    rarity: NFTRarity,
+    is_for_sale: bool,
+    price: u64,
+    created_at: i64,
+    creator: Pubkey,
+    description: String,
+    collection_id: Option<Pubkey>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> NFTData {
+        let creator = Pubkey::new_unique();
+        NFTData {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            name: "Frieren #1".to_string(),
+            symbol: "OTKV".to_string(),
+            uri: "https://example.com/1.json".to_string(),
+            anime_title: "Frieren".to_string(),
+            rarity: NFTRarity::Epic,
+            is_for_sale: true,
+            price: 2_000_000,
+            created_at: 1_700_000_000,
+            creator,
+            description: "Watched every episode".to_string(),
+            collection_id: Some(Pubkey::new_unique()),
+            attributes: vec![NFTAttribute {
+                trait_type: "Episodes".to_string(),
+                value: "28".to_string(),
+                display_type: None,
+            }],
+            royalty_basis_points: 500,
+            is_verified: true,
+            // What records from before multi-creator royalties decode to
+            creators: vec![NFTCreator { address: creator, share: 100 }],
+            listing_type: ListingType::FixedPrice,
+            payment_mint: None,
+            edition: None,
+            is_soulbound: false,
+        }
+    }
+
+    /// Encode a record as it was stored at a layout version (0 for no header)
+    fn encode_at(data: &NFTData, version: u8) -> Vec<u8> {
+        let mut buf = vec![];
+        if version > 0 {
+            buf.extend_from_slice(&NFTData::DISCRIMINATOR);
+            buf.push(version);
+        }
+        (&data.mint, &data.owner, &data.name, &data.symbol, &data.uri, &data.anime_title)
+            .serialize(&mut buf)
+            .unwrap();
+        (
+            &data.rarity,
+            data.is_for_sale,
+            data.price,
+            data.created_at,
+            &data.creator,
+            &data.description,
+            &data.collection_id,
+        )
+            .serialize(&mut buf)
+            .unwrap();
+        if version < 2 {
+            let attributes: Vec<NFTAttributeV1> = data
+                .attributes
+                .iter()
+                .map(|a| NFTAttributeV1 {
+                    trait_type: a.trait_type.clone(),
+                    value: a.value.clone(),
+                })
+                .collect();
+            attributes.serialize(&mut buf).unwrap();
+        } else {
+            data.attributes.serialize(&mut buf).unwrap();
+        }
+        (data.royalty_basis_points, data.is_verified).serialize(&mut buf).unwrap();
+        if version >= 3 {
+            data.creators.serialize(&mut buf).unwrap();
+        }
+        if version >= 4 {
+            data.listing_type.serialize(&mut buf).unwrap();
+        }
+        if version >= 5 {
+            data.payment_mint.serialize(&mut buf).unwrap();
+        }
+        if version >= 6 {
+            data.edition.serialize(&mut buf).unwrap();
+        }
+        if version >= 7 {
+            data.is_soulbound.serialize(&mut buf).unwrap();
+        }
+        buf
+    }
+
+    #[test]
+    fn current_layout_matches_encoding() {
+        let data = sample();
+        assert_eq!(data.to_packed_vec().unwrap(), encode_at(&data, NFTData::VERSION));
+    }
+
+    #[test]
+    fn unpacks_every_layout_version() {
+        let data = sample();
+        for version in 0..=NFTData::VERSION {
+            let mut stored = encode_at(&data, version);
+            // Accounts are allocated at a fixed size, so records are followed by zeroes
+            stored.resize(NFTData::LEN, 0);
+            assert_eq!(NFTData::unpack_from_slice(&stored).unwrap(), data, "version {}", version);
+            assert_eq!(NFTData::stored_version(&stored), version);
+        }
+    }
+
+    #[test]
+    fn round_trips_fields_added_since_version_1() {
+        let mut data = sample();
+        data.attributes[0].display_type = Some(DisplayType::Number);
+        data.creators = vec![
+            NFTCreator { address: Pubkey::new_unique(), share: 70 },
+            NFTCreator { address: Pubkey::new_unique(), share: 30 },
+        ];
+        data.listing_type = ListingType::DecliningPrice {
+            floor_price: 1_000_000,
+            start_at: 1_700_000_000,
+            end_at: 1_700_086_400,
+            decay: PriceDecay::Stepwise { step_seconds: 3_600 },
+        };
+        data.payment_mint = Some(Pubkey::new_unique());
+        data.edition = Some(NFTEdition::Print { master_mint: Pubkey::new_unique(), number: 3 });
+        data.is_soulbound = true;
+
+        let stored = data.to_packed_vec().unwrap();
+        assert_eq!(NFTData::unpack_from_slice(&stored).unwrap(), data);
+    }
+
+    #[test]
+    fn rejects_unknown_layout_version() {
+        let stored = encode_at(&sample(), NFTData::VERSION + 1);
+        assert_eq!(
+            NFTData::unpack_from_slice(&stored),
+            Err(OtakuVerseError::UnsupportedAccountVersion.into())
+        );
+    }
+
+    fn creator(share: u8) -> NFTCreator {
+        NFTCreator { address: Pubkey::new_unique(), share }
+    }
+
+    #[test]
+    fn validate_creators_requires_shares_summing_to_100() {
+        assert_eq!(NFTData::validate_creators(&[]), Ok(()));
+        assert_eq!(NFTData::validate_creators(&[creator(100)]), Ok(()));
+        assert_eq!(NFTData::validate_creators(&[creator(60), creator(40)]), Ok(()));
+
+        assert_eq!(
+            NFTData::validate_creators(&[creator(60), creator(39)]),
+            Err(OtakuVerseError::InvalidCreatorShares)
+        );
+        assert_eq!(
+            NFTData::validate_creators(&[creator(60), creator(41)]),
+            Err(OtakuVerseError::InvalidCreatorShares)
+        );
+        // Shares that would wrap around to 100 as a u8 are still rejected
+        assert_eq!(
+            NFTData::validate_creators(&[creator(200), creator(156)]),
+            Err(OtakuVerseError::InvalidCreatorShares)
+        );
+    }
+
+    #[test]
+    fn validate_creators_rejects_duplicates_and_too_many() {
+        let duplicate = creator(50);
+        assert_eq!(
+            NFTData::validate_creators(&[duplicate.clone(), duplicate]),
+            Err(OtakuVerseError::DuplicateCreator)
+        );
+
+        let creators: Vec<NFTCreator> = (0..=MAX_CREATORS).map(|_| creator(10)).collect();
+        assert_eq!(
+            NFTData::validate_creators(&creators),
+            Err(OtakuVerseError::TooManyCreators)
+        );
+    }
+
+    #[test]
+    fn validate_rejects_royalty_over_100_percent() {
+        let mut data = sample();
+        data.royalty_basis_points = 10000;
+        assert_eq!(data.validate(), Ok(()));
+
+        data.royalty_basis_points = 10001;
+        assert_eq!(data.validate(), Err(OtakuVerseError::InvalidBasisPoints));
+    }
+
+    #[test]
+    fn royalty_split_follows_shares() {
+        let mut data = sample();
+        data.royalty_basis_points = 1_000;
+        data.creators = vec![creator(70), creator(30)];
+
+        let split = data.royalty_split(1_000_000);
+        assert_eq!(split[0], (data.creators[0].address, 70_000));
+        assert_eq!(split[1], (data.creators[1].address, 30_000));
+    }
+
+    #[test]
+    fn royalty_split_rounds_down() {
+        let mut data = sample();
+        data.royalty_basis_points = 500;
+        data.creators = vec![creator(33), creator(33), creator(34)];
+
+        // 5% of 20_020 is 1_001, which does not split evenly
+        assert_eq!(data.calculate_royalty(20_020), 1_001);
+        let amounts: Vec<u64> = data.royalty_split(20_020).iter().map(|(_, a)| *a).collect();
+        assert_eq!(amounts, vec![330, 330, 340]);
+        // The dust stays with the seller
+        assert_eq!(amounts.iter().sum::<u64>(), 1_000);
+    }
+
+    #[test]
+    fn royalty_split_handles_large_prices() {
+        let mut data = sample();
+        data.royalty_basis_points = 10_000;
+        data.creators = vec![creator(50), creator(50)];
+
+        let split = data.royalty_split(u64::MAX);
+        assert_eq!(split[0].1, u64::MAX / 2);
+        assert_eq!(split[1].1, u64::MAX / 2);
+    }
+
+    fn declining(floor_price: u64, start_at: i64, end_at: i64, decay: PriceDecay) -> ListingType {
+        ListingType::DecliningPrice { floor_price, start_at, end_at, decay }
+    }
+
+    #[test]
+    fn fixed_price_never_changes() {
+        assert_eq!(ListingType::FixedPrice.price_at(1_000, i64::MIN), 1_000);
+        assert_eq!(ListingType::FixedPrice.price_at(1_000, i64::MAX), 1_000);
+    }
+
+    #[test]
+    fn declining_price_holds_before_start_and_floors_at_end() {
+        let listing = declining(200, 1_000, 1_100, PriceDecay::Linear);
+        assert_eq!(listing.price_at(1_000, 0), 1_000);
+        assert_eq!(listing.price_at(1_000, 1_000), 1_000);
+        assert_eq!(listing.price_at(1_000, 1_100), 200);
+        assert_eq!(listing.price_at(1_000, i64::MAX), 200);
+    }
+
+    #[test]
+    fn linear_decay_interpolates_above_floor() {
+        let listing = declining(200, 0, 100, PriceDecay::Linear);
+        assert_eq!(listing.price_at(1_000, 25), 800);
+        assert_eq!(listing.price_at(1_000, 50), 600);
+        // Rounds the drop down, so the price only reaches the floor at the end
+        assert_eq!(listing.price_at(1_000, 99), 208);
+
+        let mut previous = 1_000;
+        for now in 0..=100 {
+            let price = listing.price_at(1_000, now);
+            assert!(price >= 200 && price <= previous, "price {} at {}", price, now);
+            previous = price;
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn stepwise_decay_drops_on_step_boundaries() {
+        let listing = declining(100, 0, 100, PriceDecay::Stepwise { step_seconds: 30 });
+        assert_eq!(listing.price_at(1_000, 29), 1_000);
+        assert_eq!(listing.price_at(1_000, 30), 730);
+        assert_eq!(listing.price_at(1_000, 59), 730);
+        assert_eq!(listing.price_at(1_000, 60), 460);
+        assert_eq!(listing.price_at(1_000, 90), 190);
+        assert_eq!(listing.price_at(1_000, 99), 190);
+        assert_eq!(listing.price_at(1_000, 100), 100);
+    }
+
+    #[test]
+    fn declining_price_handles_large_values() {
+        let listing = declining(1, 0, 1_000, PriceDecay::Linear);
+        assert_eq!(listing.price_at(u64::MAX, 500), 1 << 63);
+        assert_eq!(listing.price_at(u64::MAX, 999), 18_446_744_073_709_553);
+
+        // The window spans the whole timestamp range
+        let listing = declining(1_000, i64::MIN, i64::MAX, PriceDecay::Linear);
+        assert_eq!(listing.price_at(2_000, 0), 1_500);
+    }
+
+    #[test]
+    fn validate_rejects_bad_schedules() {
+        assert_eq!(ListingType::FixedPrice.validate(0), Ok(()));
+        assert_eq!(declining(200, 0, 100, PriceDecay::Linear).validate(1_000), Ok(()));
+
+        let invalid = [
+            declining(0, 0, 100, PriceDecay::Linear),
+            declining(1_000, 0, 100, PriceDecay::Linear),
+            declining(200, 100, 100, PriceDecay::Linear),
+            declining(200, 0, 100, PriceDecay::Stepwise { step_seconds: 0 }),
+        ];
+        for listing in invalid {
+            assert_eq!(listing.validate(1_000), Err(OtakuVerseError::InvalidPriceSchedule));
+        }
+    }
+}
+
@@ -0,0 +1,40 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::account::{VersionedAccount, HEADER_LEN};
+
+/// Seed prefix for offer PDAs
+pub const OFFER_SEED: &[u8] = b"offer";
+
+/// A buyer's offer for an NFT
+///
+/// The offered lamports are held in the offer account on top of its rent.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct OfferData {
+    pub mint: Pubkey,
+    pub buyer: Pubkey,
+    /// The offered price in lamports
+    pub price: u64,
+    pub created_at: i64,
+    /// The timestamp after which the offer can no longer be accepted
+    pub expires_at: i64,
+}
+
+impl VersionedAccount for OfferData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:ofr";
+    const VERSION: u8 = 1;
+}
+
+impl OfferData {
+    pub const LEN: usize = HEADER_LEN + 32 + 32 + 8 + 8 + 8;
+
+    /// Derive the offer PDA for a mint and buyer
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[OFFER_SEED, mint.as_ref(), buyer.as_ref()], program_id)
+    }
+
+    /// Whether the offer has expired at the given timestamp
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+}
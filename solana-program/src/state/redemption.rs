@@ -0,0 +1,45 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{
+    account::{VersionedAccount, HEADER_LEN},
+    nft::{NFTRarity, MAX_ANIME_TITLE_LEN, MAX_NAME_LEN},
+};
+
+/// Seed prefix for redemption receipt PDAs
+pub const REDEMPTION_SEED: &[u8] = b"redemption";
+
+/// Receipt for an NFT burned to redeem a claim (e.g. physical merch)
+///
+/// The receipt outlives the burned NFT, so it keeps the details needed to fulfil the claim.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RedemptionData {
+    /// The mint of the burned NFT
+    pub mint: Pubkey,
+    /// The owner who burned the NFT
+    pub owner: Pubkey,
+    pub name: String,
+    pub anime_title: String,
+    pub rarity: NFTRarity,
+    pub redeemed_at: i64,
+}
+
+impl VersionedAccount for RedemptionData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:rdm";
+    const VERSION: u8 = 1;
+}
+
+impl RedemptionData {
+    pub const LEN: usize = HEADER_LEN
+        + 32
+        + 32
+        + 4 + MAX_NAME_LEN
+        + 4 + MAX_ANIME_TITLE_LEN
+        + 1
+        + 8;
+
+    /// Derive the redemption receipt PDA for a mint and owner
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[REDEMPTION_SEED, mint.as_ref(), owner.as_ref()], program_id)
+    }
+}
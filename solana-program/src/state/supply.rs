@@ -0,0 +1,152 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::{hash::hash, pubkey::Pubkey};
+
+use crate::{
+    error::OtakuVerseError,
+    state::{
+        account::{VersionedAccount, HEADER_LEN},
+        nft::{NFTRarity, MAX_ANIME_TITLE_LEN},
+    },
+};
+
+/// Seed prefix for the per-anime rarity supply PDA
+pub const RARITY_SUPPLY_SEED: &[u8] = b"rarity_supply";
+
+/// Mint counters and supply caps for each rarity tier of one anime
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RaritySupplyData {
+    pub anime_title: String,
+    /// Maximum number of NFTs per tier, indexed by `NFTRarity::index` (None if uncapped)
+    pub caps: [Option<u64>; NFTRarity::COUNT],
+    /// Number of NFTs minted per tier, indexed by `NFTRarity::index`
+    pub minted: [u64; NFTRarity::COUNT],
+}
+
+impl VersionedAccount for RaritySupplyData {
+    const DISCRIMINATOR: [u8; 8] = *b"otkv:sup";
+    const VERSION: u8 = 1;
+}
+
+impl RaritySupplyData {
+    pub const LEN: usize = HEADER_LEN
+        + 4 + MAX_ANIME_TITLE_LEN
+        + (1 + 8) * NFTRarity::COUNT
+        + 8 * NFTRarity::COUNT;
+
+    /// Derive the rarity supply PDA for an anime
+    ///
+    /// Titles can be longer than a seed, so the PDA is keyed by the title's hash.
+    pub fn find_address(program_id: &Pubkey, anime_title: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[RARITY_SUPPLY_SEED, hash(anime_title.as_bytes()).as_ref()],
+            program_id,
+        )
+    }
+
+    /// Uncapped counters for a newly tracked anime
+    pub fn new(anime_title: String) -> Self {
+        Self {
+            anime_title,
+            caps: [None; NFTRarity::COUNT],
+            minted: [0; NFTRarity::COUNT],
+        }
+    }
+
+    /// Count a mint of the given rarity, failing if its tier is sold out
+    pub fn record_mint(&mut self, rarity: &NFTRarity) -> Result<(), OtakuVerseError> {
+        let tier = rarity.index();
+        let minted = self.minted[tier]
+            .checked_add(1)
+            .ok_or(OtakuVerseError::MathOverflow)?;
+        if self.caps[tier].map_or(false, |cap| minted > cap) {
+            return Err(OtakuVerseError::RaritySupplyExhausted);
+        }
+        self.minted[tier] = minted;
+        Ok(())
+    }
+
+    /// Replace the caps, failing if any is below the number already minted
+    pub fn set_caps(
+        &mut self,
+        caps: [Option<u64>; NFTRarity::COUNT],
+    ) -> Result<(), OtakuVerseError> {
+        let below_minted = caps
+            .iter()
+            .zip(self.minted.iter())
+            .any(|(cap, minted)| cap.map_or(false, |cap| cap < *minted));
+        if below_minted {
+            return Err(OtakuVerseError::InvalidSupplyCap);
+        }
+        self.caps = caps;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_mint_counts_uncapped_tiers() {
+        let mut supply = RaritySupplyData::new("Frieren".to_string());
+        for _ in 0..3 {
+            supply.record_mint(&NFTRarity::Common).unwrap();
+        }
+        supply.record_mint(&NFTRarity::Legendary).unwrap();
+        assert_eq!(supply.minted, [3, 0, 0, 1]);
+    }
+
+    #[test]
+    fn record_mint_stops_at_cap() {
+        let mut supply = RaritySupplyData::new("Frieren".to_string());
+        supply.set_caps([None, None, None, Some(2)]).unwrap();
+
+        supply.record_mint(&NFTRarity::Legendary).unwrap();
+        supply.record_mint(&NFTRarity::Legendary).unwrap();
+        assert_eq!(
+            supply.record_mint(&NFTRarity::Legendary),
+            Err(OtakuVerseError::RaritySupplyExhausted)
+        );
+        assert_eq!(supply.minted[NFTRarity::Legendary.index()], 2);
+
+        // Other tiers are unaffected
+        supply.record_mint(&NFTRarity::Epic).unwrap();
+    }
+
+    #[test]
+    fn record_mint_rejects_zero_cap() {
+        let mut supply = RaritySupplyData::new("Frieren".to_string());
+        supply.set_caps([Some(0), None, None, None]).unwrap();
+        assert_eq!(
+            supply.record_mint(&NFTRarity::Common),
+            Err(OtakuVerseError::RaritySupplyExhausted)
+        );
+        assert_eq!(supply.minted, [0; NFTRarity::COUNT]);
+    }
+
+    #[test]
+    fn set_caps_cannot_go_below_minted() {
+        let mut supply = RaritySupplyData::new("Frieren".to_string());
+        supply.record_mint(&NFTRarity::Rare).unwrap();
+        supply.record_mint(&NFTRarity::Rare).unwrap();
+
+        assert_eq!(
+            supply.set_caps([None, Some(1), None, None]),
+            Err(OtakuVerseError::InvalidSupplyCap)
+        );
+        assert_eq!(supply.caps, [None; NFTRarity::COUNT]);
+
+        // A cap equal to the minted count sells the tier out
+        supply.set_caps([None, Some(2), None, None]).unwrap();
+        assert_eq!(
+            supply.record_mint(&NFTRarity::Rare),
+            Err(OtakuVerseError::RaritySupplyExhausted)
+        );
+
+        // Caps can be lifted again
+        supply.set_caps([None; NFTRarity::COUNT]).unwrap();
+        supply.record_mint(&NFTRarity::Rare).unwrap();
+        assert_eq!(supply.minted[NFTRarity::Rare.index()], 3);
+    }
+}
+
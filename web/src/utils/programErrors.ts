@@ -0,0 +1,116 @@
+/**
+ * Error decoding for the OtakuVerse native program
+ * Maps the program's custom error codes to readable messages.
+ *
+ * Generated from solana-program/src/error.rs (OtakuVerseError) - the codes are stable,
+ * so append new entries instead of renumbering when the program adds errors.
+ */
+
+export interface OtakuVerseErrorInfo {
+  code: number;
+  name: string;
+  message: string;
+}
+
+const OTAKUVERSE_ERRORS: Record<number, Omit<OtakuVerseErrorInfo, 'code'>> = {
+  0: { name: 'InvalidInstructionData', message: 'Invalid instruction data' },
+  1: { name: 'InsufficientFunds', message: 'Insufficient funds' },
+  2: { name: 'Unauthorized', message: 'Unauthorized' },
+  3: { name: 'NFTAlreadyExists', message: 'NFT already exists' },
+  4: { name: 'NFTNotFound', message: 'NFT not found' },
+  5: { name: 'InvalidNFTMetadata', message: 'Invalid NFT metadata' },
+  6: { name: 'InvalidMessageData', message: 'Invalid message data' },
+  7: { name: 'CommunityNotFound', message: 'Community not found' },
+  8: { name: 'NotOwner', message: 'Signer does not own the NFT' },
+  9: { name: 'NotListed', message: 'NFT is not listed for sale' },
+  10: { name: 'AlreadyListed', message: 'NFT is already listed for sale' },
+  11: { name: 'PriceMismatch', message: 'Price does not match the listing' },
+  12: { name: 'StringTooLong', message: 'String exceeds its maximum length' },
+  13: { name: 'AccountAlreadyInitialized', message: 'Account is already initialized' },
+  14: { name: 'WrongAccountOwner', message: 'Account is not owned by the expected program' },
+  15: { name: 'InvalidPDA', message: 'Account does not match its expected program address' },
+  16: { name: 'InvalidBasisPoints', message: 'Basis points must not exceed 10000' },
+  17: { name: 'InvalidPrice', message: 'Price must be greater than zero' },
+  18: { name: 'MintMismatch', message: 'Mint does not match the NFT' },
+  19: { name: 'NotAdmin', message: 'Signer is not a platform admin' },
+  20: { name: 'AdminAlreadyExists', message: 'Admin is already registered' },
+  21: { name: 'AdminNotFound', message: 'Admin is not registered' },
+  22: { name: 'AdminListFull', message: 'Admin registry is full' },
+  23: { name: 'TreasuryMismatch', message: 'Treasury does not match the program config' },
+  24: { name: 'CreatorMismatch', message: 'Creator does not match the NFT' },
+  25: { name: 'CommunityAlreadyExists', message: 'Community already exists' },
+  26: { name: 'NotCommunityCreator', message: 'Signer is not the community creator' },
+  27: { name: 'NotModerator', message: 'Signer is not a community moderator' },
+  28: { name: 'NotMember', message: 'User is not a community member' },
+  29: { name: 'AlreadyMember', message: 'User is already a community member' },
+  30: { name: 'UserBanned', message: 'User is banned from the community' },
+  31: { name: 'CannotBan', message: 'User cannot be banned by this signer' },
+  32: { name: 'ModeratorAlreadyExists', message: 'Moderator is already appointed' },
+  33: { name: 'ModeratorNotFound', message: 'Moderator is not appointed' },
+  34: { name: 'ModeratorListFull', message: 'Moderator list is full' },
+  35: { name: 'EmptyMessage', message: 'Message is empty' },
+  36: { name: 'InvalidReplyTarget', message: 'Reply target is not a message in this community' },
+  37: { name: 'TooManyReactions', message: 'Message has reached its limit of distinct reactions' },
+  38: { name: 'ReactionAlreadyExists', message: 'Reaction already exists' },
+  39: { name: 'MathOverflow', message: 'Arithmetic overflow' },
+  40: { name: 'UnsupportedInstructionVersion', message: 'Unsupported instruction version' },
+  41: { name: 'UnknownInstruction', message: 'Unknown instruction' },
+  42: { name: 'UnsupportedAccountVersion', message: 'Unsupported account version' },
+  43: { name: 'AccountNeedsMigration', message: 'Account must be migrated to the current layout' },
+  44: { name: 'NameTooLong', message: 'NFT name exceeds 32 bytes' },
+  45: { name: 'SymbolTooLong', message: 'NFT symbol exceeds 10 bytes' },
+  46: { name: 'UriTooLong', message: 'NFT URI exceeds 200 bytes' },
+  47: { name: 'AnimeTitleTooLong', message: 'Anime title exceeds 64 bytes' },
+  48: { name: 'DescriptionTooLong', message: 'NFT description exceeds 256 bytes' },
+  49: { name: 'TooManyAttributes', message: 'NFT has more than 16 attributes' },
+  50: { name: 'AttributeTooLong', message: 'Attribute trait type or value is too long' },
+  51: { name: 'InvalidRarity', message: 'Unknown NFT rarity' },
+  52: { name: 'RaritySupplyExhausted', message: 'Supply cap for this rarity has been reached' },
+  53: { name: 'InvalidSupplyCap', message: 'Supply cap is below the number already minted' },
+  54: { name: 'NotCollectionAuthority', message: 'Signer is not the collection authority' },
+  55: { name: 'DuplicateAttribute', message: 'NFT already has an attribute with this trait type' },
+  56: { name: 'AttributeNotFound', message: 'NFT has no attribute with this trait type' },
+  57: { name: 'InvalidAttributeValue', message: 'Attribute value does not match its display type' },
+  58: { name: 'TooManyCreators', message: 'NFT has more than 5 creators' },
+  59: { name: 'InvalidCreatorShares', message: 'Creator shares must sum to 100' },
+  60: { name: 'DuplicateCreator', message: 'Creator is listed more than once' },
+  61: { name: 'OfferExpired', message: 'Offer has expired' },
+  62: { name: 'InvalidExpiry', message: 'Expiry must be in the future' },
+  63: { name: 'AuctionNotActive', message: 'Auction is not accepting bids' },
+  64: { name: 'AuctionNotEnded', message: 'Auction has not ended' },
+  65: { name: 'BidTooLow', message: 'Bid is below the reserve price or minimum increment' },
+  66: { name: 'InvalidAuctionSchedule', message: 'Auction must end after it starts, in the future' },
+  67: { name: 'BidderMismatch', message: 'Account is not the auction\'s highest bidder' },
+  68: { name: 'InvalidPriceSchedule', message: 'Declining price must fall to a nonzero lower floor over a non-empty window' },
+  69: { name: 'PaymentMintNotAccepted', message: 'Payment mint is not accepted' },
+  70: { name: 'PaymentMintAlreadyAccepted', message: 'Payment mint is already accepted' },
+  71: { name: 'PaymentMintListFull', message: 'Payment mint allowlist is full' },
+  72: { name: 'AlreadyAnEdition', message: 'NFT is already a master edition or a print' },
+  73: { name: 'NotMasterEdition', message: 'NFT is not a master edition' },
+  74: { name: 'EditionMismatch', message: 'Edition accounts do not match the NFT\'s edition' },
+  75: { name: 'SoulboundNFT', message: 'Soulbound NFTs cannot be transferred' },
+  76: { name: 'NotSoulbound', message: 'NFT is not soulbound' },
+  77: { name: 'RaritySupplyNotSet', message: 'Supply caps have not been set for this anime' },
+};
+
+// Look up a custom error code returned by the program
+export const errorForCode = (code: number): OtakuVerseErrorInfo | null => {
+  const entry = OTAKUVERSE_ERRORS[code];
+  return entry ? { code, ...entry } : null;
+};
+
+// Extract the custom error code from a failed transaction's error or logs
+export const decodeProgramError = (error: unknown): OtakuVerseErrorInfo | null => {
+  const text = error instanceof Error ? error.message : String(error);
+  const match = text.match(/custom program error: 0x([0-9a-f]+)/i);
+  if (match) {
+    return errorForCode(parseInt(match[1], 16));
+  }
+
+  const custom = (error as { InstructionError?: [number, { Custom?: number }] })?.InstructionError;
+  if (custom && typeof custom[1]?.Custom === 'number') {
+    return errorForCode(custom[1].Custom);
+  }
+
+  return null;
+};